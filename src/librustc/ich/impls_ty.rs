@@ -468,6 +468,11 @@ for ::mir::interpret::Scalar {
     }
 }
 
+impl_stable_hash_for!(enum ::mir::interpret::ScalarMaybeUndef {
+    Scalar(v),
+    Undef
+});
+
 impl_stable_hash_for!(struct ty::Const<'tcx> {
     ty,
     val
@@ -511,11 +516,16 @@ for ::mir::interpret::EvalErrorKind<'gcx, O> {
             InvalidMemoryAccess |
             InvalidFunctionPointer |
             InvalidBool |
+            InvalidNonZero |
+            UnevaluatedConstant |
+            UnexpectedConstValueKind |
+            InvalidClampRange |
             InvalidNullPointerUsage |
             ReadPointerAsBytes |
             ReadBytesAsPointer |
             ReadForeignStatic |
             InvalidPointerMath |
+            PointerArithOverflow |
             DeadLocal |
             StackFrameLimitReached |
             OutOfTls |
@@ -579,6 +589,20 @@ for ::mir::interpret::EvalErrorKind<'gcx, O> {
             },
             Intrinsic(ref s) => s.hash_stable(hcx, hasher),
             InvalidChar(c) => c.hash_stable(hcx, hasher),
+            InvalidFloatSize {
+                expected,
+                found,
+            } => {
+                expected.hash_stable(hcx, hasher);
+                found.hash_stable(hcx, hasher)
+            },
+            ScalarSizeMismatch {
+                expected,
+                found,
+            } => {
+                expected.hash_stable(hcx, hasher);
+                found.hash_stable(hcx, hasher)
+            },
             AbiViolation(ref s) => s.hash_stable(hcx, hasher),
             AlignmentCheckFailed {
                 required,
@@ -63,9 +63,11 @@
 #![feature(unboxed_closures)]
 #![feature(trace_macros)]
 #![feature(trusted_len)]
+#![feature(try_from)]
 #![feature(vec_remove_item)]
 #![feature(step_trait)]
 #![feature(integer_atomics)]
+#![feature(reverse_bits)]
 #![feature(test)]
 #![feature(impl_header_lifetime_elision)]
 #![feature(in_band_lifetimes)]
@@ -194,6 +194,7 @@ pub enum EvalErrorKind<'tcx, O> {
     InvalidMemoryAccess,
     InvalidFunctionPointer,
     InvalidBool,
+    InvalidNonZero,
     InvalidDiscriminant(u128),
     PointerOutOfBounds {
         ptr: Pointer,
@@ -205,6 +206,7 @@ pub enum EvalErrorKind<'tcx, O> {
     ReadBytesAsPointer,
     ReadForeignStatic,
     InvalidPointerMath,
+    PointerArithOverflow,
     ReadUndefBytes(Size),
     DeadLocal,
     InvalidBoolOp(mir::BinOp),
@@ -218,6 +220,21 @@ pub enum EvalErrorKind<'tcx, O> {
     RemainderByZero,
     Intrinsic(String),
     InvalidChar(u128),
+    InvalidFloatSize {
+        expected: Size,
+        found: Size,
+    },
+    ScalarSizeMismatch {
+        expected: Size,
+        found: Size,
+    },
+    /// A constant was used before it was evaluated
+    UnevaluatedConstant,
+    /// A constant is not represented as the operation using it requires, e.g. a `ByRef` where
+    /// a `Scalar` was expected
+    UnexpectedConstValueKind,
+    /// `clamp` was called with `min > max` or a NaN bound
+    InvalidClampRange,
     StackFrameLimitReached,
     OutOfTls,
     TlsOutOfBounds,
@@ -306,6 +323,8 @@ impl<'tcx, O> EvalErrorKind<'tcx, O> {
                 "tried to use a function pointer after offsetting it",
             InvalidBool =>
                 "invalid boolean value read",
+            InvalidNonZero =>
+                "read zero where a nonzero value was required",
             InvalidDiscriminant(..) =>
                 "invalid enum discriminant value read",
             PointerOutOfBounds { .. } =>
@@ -331,6 +350,9 @@ impl<'tcx, O> EvalErrorKind<'tcx, O> {
             InvalidPointerMath =>
                 "attempted to do invalid arithmetic on pointers that would leak base addresses, \
                 e.g. comparing pointers into different allocations",
+            PointerArithOverflow =>
+                "overflowing pointer arithmetic: the total offset in bytes does not fit in the \
+                address space",
             ReadUndefBytes(_) =>
                 "attempted to read undefined bytes",
             DeadLocal =>
@@ -350,6 +372,16 @@ impl<'tcx, O> EvalErrorKind<'tcx, O> {
                 "mir not found",
             InvalidChar(..) =>
                 "tried to interpret an invalid 32-bit value as a char",
+            InvalidFloatSize { .. } =>
+                "tried to interpret a value of the wrong size as a float",
+            ScalarSizeMismatch { .. } =>
+                "scalar size does not match the size of its type",
+            UnevaluatedConstant =>
+                "tried to use the value of a constant that has not been evaluated yet",
+            UnexpectedConstValueKind =>
+                "constant does not have the representation the operation requires",
+            InvalidClampRange =>
+                "tried to clamp a value to a range with `min > max` or a NaN bound",
             StackFrameLimitReached =>
                 "reached the configured maximum number of stack frames",
             OutOfTls =>
@@ -480,8 +512,21 @@ impl<'tcx, O: fmt::Debug> fmt::Debug for EvalErrorKind<'tcx, O> {
                 write!(f, "tried to deallocate {} memory but gave {} as the kind", old, new),
             Intrinsic(ref err) =>
                 write!(f, "{}", err),
-            InvalidChar(c) =>
-                write!(f, "tried to interpret an invalid 32-bit value as a char: {}", c),
+            InvalidChar(c) => {
+                let reason = if c >= 0xD800 && c <= 0xDFFF {
+                    "a surrogate code point"
+                } else {
+                    "out of range"
+                };
+                write!(f, "tried to interpret an invalid 32-bit value as a char: {} ({})",
+                       c, reason)
+            }
+            InvalidFloatSize { expected, found } =>
+                write!(f, "tried to interpret a {}-bit value as a {}-bit float",
+                       found.bits(), expected.bits()),
+            ScalarSizeMismatch { expected, found } =>
+                write!(f, "scalar has size {} but its type has size {}",
+                       found.bytes(), expected.bytes()),
             AlignmentCheckFailed { required, has } =>
                write!(f, "tried to access memory with alignment {}, but alignment {} is required",
                       has.abi(), required.abi()),
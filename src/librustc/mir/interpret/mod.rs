@@ -23,7 +23,7 @@ pub use self::error::{
     FrameInfo, ConstEvalResult,
 };
 
-pub use self::value::{Scalar, ConstValue};
+pub use self::value::{Scalar, ConstValue, ScalarMaybeUndef, ScalarKind};
 
 use std::fmt;
 use mir;
@@ -543,6 +543,19 @@ impl Allocation {
             mutability: Mutability::Mutable,
         }
     }
+
+    /// Returns all relocations overlapping with the `size` bytes starting at `offset`.
+    pub fn relocations_in(
+        &self,
+        offset: Size,
+        size: Size,
+        cx: impl HasDataLayout,
+    ) -> &[(Size, AllocId)] {
+        // We have to go back `pointer_size - 1` bytes, as that one would still overlap with
+        // the beginning of this range.
+        let start = offset.bytes().saturating_sub(cx.data_layout().pointer_size.bytes() - 1);
+        self.relocations.range(Size::from_bytes(start)..offset + size)
+    }
 }
 
 impl<'tcx> ::serialize::UseSpecializedDecodable for &'tcx Allocation {}
@@ -619,6 +632,14 @@ pub fn truncate(value: u128, size: Size) -> u128 {
     (value << shift) >> shift
 }
 
+/// The mask with the lowest `size.bits()` bits set, e.g. `0xff` for a size of one byte.
+pub fn bitmask(size: Size) -> u128 {
+    match size.bits() {
+        0 => 0,
+        bits => u128::max_value() >> (128 - bits),
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Undefined byte tracking
 ////////////////////////////////////////////////////////////////////////////////
@@ -10,11 +10,22 @@
 
 #![allow(unknown_lints)]
 
-use ty::layout::{HasDataLayout, Size};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use ty::layout::{self, Align, HasDataLayout, Size, TargetDataLayout};
 use ty::subst::Substs;
 use hir::def_id::DefId;
+use mir;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_apfloat::ieee::{Double, Single};
+use rustc_apfloat::Float;
 
-use super::{EvalResult, Pointer, PointerArithmetic, Allocation, AllocId, sign_extend, truncate};
+use super::{
+    EvalResult, EvalErrorKind, Pointer, PointerArithmetic, Allocation, AllocId,
+    sign_extend, truncate, bitmask, read_target_uint, write_target_uint,
+};
 
 /// Represents a constant value in Rust. Scalar and ScalarPair are optimizations which
 /// matches the LocalValue optimizations for easy conversions between Value and ConstValue.
@@ -40,6 +51,22 @@ pub enum ConstValue<'tcx> {
     ByRef(AllocId, &'tcx Allocation, Size),
 }
 
+/// The category of a primitive type, for rendering values without access to a `TyCtxt`, see
+/// `ConstValue::render_as`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScalarKind {
+    Bool,
+    Char,
+    /// A signed integer of the given size.
+    I(Size),
+    /// An unsigned integer of the given size.
+    U(Size),
+    F32,
+    F64,
+    /// A thin pointer, which may also be an integer cast to a pointer.
+    Ptr,
+}
+
 impl<'tcx> ConstValue<'tcx> {
     #[inline]
     pub fn try_to_scalar(&self) -> Option<Scalar> {
@@ -51,16 +78,223 @@ impl<'tcx> ConstValue<'tcx> {
         }
     }
 
+    #[inline]
+    pub fn try_to_scalar_pair(&self) -> Option<(Scalar, Scalar)> {
+        match *self {
+            ConstValue::Unevaluated(..) |
+            ConstValue::ByRef(..) |
+            ConstValue::Scalar(..) => None,
+            ConstValue::ScalarPair(a, b) => Some((a, b)),
+        }
+    }
+
+    /// Splits a fat pointer into `(data, metadata)`, the order in which `new_slice`, `new_str`
+    /// and `new_dyn_trait` store them. The metadata is a length or a vtable pointer.
+    #[inline]
+    pub fn fat_pointer_parts(&self) -> Option<(Scalar, Scalar)> {
+        self.try_to_scalar_pair()
+    }
+
+    /// Reads both halves of a `ScalarPair` of two integers, e.g. an `(u64, u64)`. Returns `None`
+    /// if either half is a pointer or does not have the expected size.
+    pub fn scalar_pair_bits(&self, a_size: Size, b_size: Size) -> Option<(u128, u128)> {
+        let bits = |val: Scalar, expected: Size| match val.to_bits_any() {
+            Ok((bits, size)) if size == expected => Some(bits),
+            _ => None,
+        };
+        let (a, b) = self.try_to_scalar_pair()?;
+        Some((bits(a, a_size)?, bits(b, b_size)?))
+    }
+
     #[inline]
     pub fn try_to_bits(&self, size: Size) -> Option<u128> {
         self.try_to_scalar()?.to_bits(size).ok()
     }
 
+    /// Like `try_to_bits`, but reports why the constant is not an integer of `size`.
+    pub fn to_scalar_int(&self, size: Size) -> EvalResult<'tcx, u128> {
+        match *self {
+            ConstValue::Scalar(Scalar::Bits { bits, size: found }) => {
+                if found as u64 != size.bytes() {
+                    return err!(ScalarSizeMismatch {
+                        expected: size,
+                        found: Size::from_bytes(found as u64),
+                    });
+                }
+                Ok(bits)
+            }
+            ConstValue::Scalar(Scalar::Ptr(_)) => err!(ReadPointerAsBytes),
+            ConstValue::Unevaluated(..) => err!(UnevaluatedConstant),
+            ConstValue::ScalarPair(..) |
+            ConstValue::ByRef(..) => err!(UnexpectedConstValueKind),
+        }
+    }
+
+    #[inline]
+    pub fn try_to_bool(&self) -> Option<bool> {
+        self.try_to_scalar()?.to_bool().ok()
+    }
+
+    #[inline]
+    pub fn try_to_f32(&self) -> Option<f32> {
+        self.try_to_scalar()?.to_f32().ok()
+    }
+
+    #[inline]
+    pub fn try_to_f64(&self) -> Option<f64> {
+        self.try_to_scalar()?.to_f64().ok()
+    }
+
     #[inline]
     pub fn try_to_ptr(&self) -> Option<Pointer> {
         self.try_to_scalar()?.to_ptr().ok()
     }
 
+    /// Like `try_to_ptr`, but returns the allocation and the offset into it separately.
+    #[inline]
+    pub fn try_to_ptr_parts(&self) -> Option<(AllocId, Size)> {
+        let ptr = self.try_to_ptr()?;
+        Some((ptr.alloc_id, ptr.offset))
+    }
+
+    /// Moves the offset of a `ByRef` forward by `delta`, e.g. to project to a field. The new
+    /// offset may point at the end of the allocation, but not beyond it. Errors for all other
+    /// variants, since they are not stored in memory.
+    pub fn offset_byref(self, delta: Size, cx: impl HasDataLayout) -> EvalResult<'tcx, Self> {
+        match self {
+            ConstValue::ByRef(id, alloc, offset) => {
+                let new_offset = match offset.checked_add(delta, cx) {
+                    Some(new_offset) => new_offset,
+                    None => return err!(PointerArithOverflow),
+                };
+                let allocation_size = Size::from_bytes(alloc.bytes.len() as u64);
+                if new_offset > allocation_size {
+                    return err!(PointerOutOfBounds {
+                        ptr: Pointer::new(id, new_offset),
+                        access: false,
+                        allocation_size,
+                    });
+                }
+                Ok(ConstValue::ByRef(id, alloc, new_offset))
+            }
+            ConstValue::Unevaluated(..) |
+            ConstValue::Scalar(..) |
+            ConstValue::ScalarPair(..) => err!(InvalidMemoryAccess),
+        }
+    }
+
+    /// The number of bytes this constant spans in memory. For `ByRef` this is the part of the
+    /// allocation starting at the offset, for scalars it is their size. Returns `None` for
+    /// `Unevaluated` constants.
+    pub fn byte_len(&self, cx: impl HasDataLayout) -> Option<u64> {
+        let dl = cx.data_layout();
+        match *self {
+            ConstValue::Unevaluated(..) => None,
+            ConstValue::ByRef(_, alloc, offset) => {
+                (alloc.bytes.len() as u64).checked_sub(offset.bytes())
+            }
+            ConstValue::Scalar(val) => Some(val.size_and_align(dl).0.bytes()),
+            ConstValue::ScalarPair(a, b) => {
+                Some(a.size_and_align(dl).0.bytes() + b.size_and_align(dl).0.bytes())
+            }
+        }
+    }
+
+    /// The allocation this constant points to or lives in. For a `ScalarPair` this is the
+    /// allocation of the first of its scalars that is a pointer, e.g. the data pointer of a fat
+    /// pointer rather than its vtable.
+    pub fn alloc_id(&self) -> Option<AllocId> {
+        let ptr_id = |val: Scalar| match val {
+            Scalar::Ptr(ptr) => Some(ptr.alloc_id),
+            Scalar::Bits { .. } => None,
+        };
+        match *self {
+            ConstValue::Unevaluated(..) => None,
+            ConstValue::ByRef(id, ..) => Some(id),
+            ConstValue::Scalar(val) => ptr_id(val),
+            ConstValue::ScalarPair(a, b) => ptr_id(a).or_else(|| ptr_id(b)),
+        }
+    }
+
+    /// Reads the length out of a slice constant built by `new_slice`.
+    #[inline]
+    pub fn try_to_slice_len(&self, cx: impl HasDataLayout) -> Option<u64> {
+        let (_, len) = self.try_to_scalar_pair()?;
+        len.to_usize(cx).ok()
+    }
+
+    /// Returns the bytes of a byte string constant. For a `&[u8]` or `&str` slice these are the
+    /// bytes its data pointer points to, where `lookup` has to return the allocation for the
+    /// pointer. For a `ByRef` these are the bytes from its offset to the end of its allocation.
+    ///
+    /// Returns `None` if this is not a byte string, or if the bytes contain pointers or
+    /// undefined bytes.
+    pub fn to_byte_slice(
+        &self,
+        lookup: impl FnOnce(AllocId) -> Option<&'tcx Allocation>,
+        cx: impl HasDataLayout,
+    ) -> Option<&'tcx [u8]> {
+        let dl = cx.data_layout();
+        let (alloc, offset, size) = match *self {
+            ConstValue::ScalarPair(ptr, len) => {
+                let len = len.to_usize(dl).ok()?;
+                if len == 0 {
+                    // the data pointer of an empty slice need not point to an allocation
+                    return Some(&[]);
+                }
+                let ptr = ptr.to_ptr().ok()?;
+                (lookup(ptr.alloc_id)?, ptr.offset, Size::from_bytes(len))
+            }
+            ConstValue::ByRef(_, alloc, offset) => {
+                let len = Size::from_bytes(alloc.bytes.len() as u64);
+                if offset > len {
+                    return None;
+                }
+                (alloc, offset, len - offset)
+            }
+            ConstValue::Unevaluated(..) |
+            ConstValue::Scalar(..) => return None,
+        };
+        let end = offset.checked_add(size, dl)?;
+        if end.bytes() > alloc.bytes.len() as u64 {
+            return None;
+        }
+        if !alloc.relocations_in(offset, size, dl).is_empty() {
+            return None;
+        }
+        alloc.undef_mask.is_range_defined(offset, end).ok()?;
+        Some(&alloc.bytes[offset.bytes() as usize..end.bytes() as usize])
+    }
+
+    /// Compares the bytes of a `&[u8]` or `&str` constant with `other`, e.g. to match it against
+    /// a byte string pattern. The bytes are read like `to_byte_slice` does.
+    pub fn slice_bytes_eq(
+        &self,
+        other: &[u8],
+        lookup: impl FnOnce(AllocId) -> Option<&'tcx Allocation>,
+        cx: impl HasDataLayout,
+    ) -> Option<bool> {
+        Some(self.to_byte_slice(lookup, cx)? == other)
+    }
+
+    /// Whether the memory of a `ByRef`, from its offset to the end of the allocation, contains
+    /// any pointers. If it does not, the bytes can be emitted as they are. Always `false` for
+    /// the other variants, as they are not backed by memory.
+    pub fn has_relocations_in_range(&self, cx: impl HasDataLayout) -> bool {
+        match *self {
+            ConstValue::ByRef(_, alloc, offset) => {
+                let len = Size::from_bytes(alloc.bytes.len() as u64);
+                if offset >= len {
+                    return false;
+                }
+                !alloc.relocations_in(offset, len - offset, cx).is_empty()
+            }
+            ConstValue::Unevaluated(..) |
+            ConstValue::Scalar(..) |
+            ConstValue::ScalarPair(..) => false,
+        }
+    }
+
     #[inline]
     pub fn new_slice(
         val: Scalar,
@@ -73,36 +307,430 @@ impl<'tcx> ConstValue<'tcx> {
         })
     }
 
+    /// Creates the `ScalarPair` representation of a `&str`: a pointer to the UTF-8 bytes and
+    /// their length in bytes. This is currently the same as `new_slice` for a `&[u8]`.
+    #[inline]
+    pub fn new_str(
+        ptr: Scalar,
+        len: u64,
+        cx: impl HasDataLayout
+    ) -> Self {
+        ConstValue::new_slice(ptr, len, cx)
+    }
+
     #[inline]
     pub fn new_dyn_trait(val: Scalar, vtable: Pointer) -> Self {
         ConstValue::ScalarPair(val, Scalar::Ptr(vtable))
     }
+
+    /// Creates a `ScalarPair` for any value with `layout::abi::ScalarPair`, e.g. a 2-tuple of
+    /// integers. Prefer `new_slice`, `new_str` and `new_dyn_trait` for fat pointers.
+    #[inline]
+    pub fn new_pair(a: Scalar, b: Scalar) -> Self {
+        ConstValue::ScalarPair(a, b)
+    }
+
+    /// Whether this is a `Scalar` of `expected` size, as a cheap sanity check before trusting the
+    /// result of const evaluation. Pointers match if `expected` is the pointer size of `cx`.
+    /// Always `false` for the other variants.
+    pub fn scalar_size_matches(&self, expected: Size, cx: impl HasDataLayout) -> bool {
+        match *self {
+            ConstValue::Scalar(Scalar::Bits { size, .. }) => size as u64 == expected.bytes(),
+            ConstValue::Scalar(Scalar::Ptr(_)) => expected == cx.data_layout().pointer_size,
+            ConstValue::Unevaluated(..) |
+            ConstValue::ScalarPair(..) |
+            ConstValue::ByRef(..) => false,
+        }
+    }
+
+    /// Whether this is the value of a zero-sized type. Always `false` for `Unevaluated`, as its
+    /// size is not known yet.
+    #[inline]
+    pub fn is_zst(&self) -> bool {
+        match *self {
+            ConstValue::Scalar(Scalar::Bits { size: 0, .. }) => true,
+            ConstValue::ByRef(_, alloc, _) => alloc.bytes.is_empty(),
+            ConstValue::Unevaluated(..) |
+            ConstValue::Scalar(..) |
+            ConstValue::ScalarPair(..) => false,
+        }
+    }
+
+    /// Applies `f` to the immediate scalars of this value. `ByRef` values are returned unchanged,
+    /// their memory (including any pointers in it) is not visited.
+    pub fn map_scalars(self, mut f: impl FnMut(Scalar) -> Scalar) -> Self {
+        match self {
+            ConstValue::Scalar(val) => ConstValue::Scalar(f(val)),
+            ConstValue::ScalarPair(a, b) => ConstValue::ScalarPair(f(a), f(b)),
+            ConstValue::Unevaluated(..) |
+            ConstValue::ByRef(..) => self,
+        }
+    }
+
+    /// Rewrites all `AllocId`s stored directly in this value using `map`, e.g. when loading
+    /// a constant from another crate's metadata.
+    ///
+    /// The relocations of the `Allocation` referenced by a `ByRef` are *not* touched, since that
+    /// allocation is interned and shared. Remapping those is the caller's responsibility.
+    pub fn relocate(self, map: &impl Fn(AllocId) -> AllocId) -> Self {
+        self.fold_alloc_ids(&mut |id| map(id))
+    }
+
+    /// Like `relocate`, but takes a stateful closure, e.g. for an interner that builds up the
+    /// mapping as it goes. Ids are visited in order: the `ByRef` id, or the pointers of the
+    /// scalars from left to right.
+    pub fn fold_alloc_ids(self, f: &mut impl FnMut(AllocId) -> AllocId) -> Self {
+        match self {
+            ConstValue::ByRef(id, alloc, offset) => ConstValue::ByRef(f(id), alloc, offset),
+            _ => self.map_scalars(|val| match val {
+                Scalar::Ptr(ptr) => Scalar::Ptr(Pointer::new(f(ptr.alloc_id), ptr.offset)),
+                Scalar::Bits { .. } => val,
+            }),
+        }
+    }
+
+    /// Renders the value in a compact, human readable form for debug dumps. Unlike the `Debug`
+    /// output, this shows integers in both decimal and hexadecimal and only the size of the
+    /// memory behind a `ByRef`.
+    pub fn fmt_pretty(&self) -> String {
+        fn scalar(val: Scalar) -> String {
+            match val {
+                Scalar::Bits { bits, .. } => format!("{} ({})", bits, val.fmt_hex().unwrap()),
+                Scalar::Ptr(ptr) => format!("alloc{}+{}", ptr.alloc_id, ptr.offset.bytes()),
+            }
+        }
+        match *self {
+            ConstValue::Unevaluated(def_id, _) => format!("<unevaluated {:?}>", def_id),
+            ConstValue::Scalar(val) => scalar(val),
+            ConstValue::ScalarPair(a, b) => format!("({}, {})", scalar(a), scalar(b)),
+            ConstValue::ByRef(id, alloc, offset) => {
+                format!("&alloc{}+{} ({} bytes)", id, offset.bytes(), alloc.bytes.len())
+            }
+        }
+    }
+
+    /// Renders a scalar constant as a value of the primitive type `kind`, the way MIR dumps
+    /// print typed constants, e.g. `true`, `'a'`, `-3i8` or `1f32`. Returns `None` if this is not
+    /// a `Scalar` or if its representation does not fit `kind`.
+    pub fn render_as(&self, kind: ScalarKind, cx: impl HasDataLayout) -> Option<String> {
+        let val = self.try_to_scalar()?;
+        let bits = |size: Size| match val.to_bits_any() {
+            Ok((bits, s)) if s == size && size.bytes() != 0 => Some(bits),
+            _ => None,
+        };
+        Some(match kind {
+            ScalarKind::Bool => val.to_bool().ok()?.to_string(),
+            ScalarKind::Char => {
+                format!("{:?}", ::std::char::from_u32(bits(Size::from_bytes(4))? as u32)?)
+            }
+            ScalarKind::I(size) => {
+                format!("{}i{}", sign_extend(bits(size)?, size) as i128, size.bits())
+            }
+            ScalarKind::U(size) => format!("{}u{}", bits(size)?, size.bits()),
+            ScalarKind::F32 => format!("{}f32", Single::from_bits(bits(Size::from_bytes(4))?)),
+            ScalarKind::F64 => format!("{}f64", Double::from_bits(bits(Size::from_bytes(8))?)),
+            ScalarKind::Ptr => match val {
+                Scalar::Ptr(ptr) => format!("alloc{}+{}", ptr.alloc_id, ptr.offset.bytes()),
+                Scalar::Bits { .. } => format!("{:#x}", bits(cx.data_layout().pointer_size)?),
+            },
+        })
+    }
+
+    /// Structural equality that ignores which allocations pointers point to: two pointers are
+    /// considered equal if their offsets are, even if their `AllocId`s differ. The memory of
+    /// `ByRef`s is compared byte-wise from the offset to the end of the allocation, including
+    /// which bytes are undefined and where pointers are stored, but again not their `AllocId`s.
+    /// This includes pointers that start before the offset but overlap with the compared bytes.
+    ///
+    /// This is a strictly weaker relation than `==` and must not be used where the identity of
+    /// an allocation matters.
+    pub fn eq_ignoring_provenance(&self, other: &Self, cx: impl HasDataLayout) -> bool {
+        fn scalar_eq(a: Scalar, b: Scalar) -> bool {
+            match (a, b) {
+                (Scalar::Ptr(a), Scalar::Ptr(b)) => a.offset == b.offset,
+                _ => a == b,
+            }
+        }
+        fn memory<'a>(
+            alloc: &'a Allocation,
+            offset: Size,
+            dl: &TargetDataLayout,
+        ) -> (&'a [u8], Vec<bool>, Vec<i64>) {
+            let start = offset.bytes().min(alloc.bytes.len() as u64);
+            let len = Size::from_bytes(alloc.bytes.len() as u64 - start);
+            let bytes = &alloc.bytes[start as usize..];
+            let defined = (start..alloc.bytes.len() as u64)
+                .map(|i| alloc.undef_mask.get(Size::from_bytes(i)))
+                .collect();
+            // a pointer starting before `offset` is stored at a negative relative offset
+            let relocations = alloc.relocations_in(Size::from_bytes(start), len, dl).iter()
+                .map(|&(reloc_offset, _)| reloc_offset.bytes() as i64 - start as i64)
+                .collect();
+            (bytes, defined, relocations)
+        }
+        let dl = cx.data_layout();
+        match (*self, *other) {
+            (ConstValue::Scalar(a), ConstValue::Scalar(b)) => scalar_eq(a, b),
+            (ConstValue::ScalarPair(a1, a2), ConstValue::ScalarPair(b1, b2)) => {
+                scalar_eq(a1, b1) && scalar_eq(a2, b2)
+            }
+            (ConstValue::ByRef(_, a, a_offset), ConstValue::ByRef(_, b, b_offset)) => {
+                memory(a, a_offset, dl) == memory(b, b_offset, dl)
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Writes a `Scalar` or `ScalarPair` into a fresh allocation and returns it together with
+    /// the offset of the value in it. The allocation is laid out like a value with `Scalar` or
+    /// `ScalarPair` ABI would be, assuming each scalar is aligned like an integer of its size:
+    /// the second scalar of a pair starts at the first suitably aligned offset after the first
+    /// one. Pointers are stored as relocations. A `ByRef` simply returns a copy of its
+    /// allocation and its offset.
+    ///
+    /// Must not be called on `Unevaluated`, which has no value to write yet.
+    pub fn force_to_byref(self, cx: impl HasDataLayout) -> (Allocation, Size) {
+        match self {
+            ConstValue::Unevaluated(..) => {
+                bug!("cannot write a value to memory that has not been evaluated: {:?}", self)
+            }
+            ConstValue::ByRef(_, alloc, offset) => (alloc.clone(), offset),
+            ConstValue::Scalar(val) => {
+                let (size, align) = val.size_and_align(cx);
+                let mut alloc = Allocation::undef(size, align);
+                val.write_to_alloc(&mut alloc, Size::ZERO, cx);
+                (alloc, Size::ZERO)
+            }
+            ConstValue::ScalarPair(a, b) => {
+                let (a_size, a_align) = a.size_and_align(cx);
+                let (b_size, b_align) = b.size_and_align(cx);
+                let b_offset = a_size.abi_align(b_align);
+                let align = a_align.max(b_align);
+                let mut alloc = Allocation::undef((b_offset + b_size).abi_align(align), align);
+                a.write_to_alloc(&mut alloc, Size::ZERO, cx);
+                b.write_to_alloc(&mut alloc, b_offset, cx);
+                (alloc, Size::ZERO)
+            }
+        }
+    }
+
+    /// Serializes a `ScalarPair` into its bytes and the offsets and targets of its pointers. The
+    /// bytes of a pointer hold its offset, padding bytes are zero.
+    ///
+    /// `a_size` and `b_size` are the sizes the two halves are expected to have; they are only
+    /// checked, not used for the layout. The layout is the one `force_to_byref` uses, with
+    /// each half aligned like an integer of its size.
+    pub fn scalar_pair_to_bytes(
+        &self,
+        a_size: Size,
+        b_size: Size,
+        cx: impl HasDataLayout,
+    ) -> EvalResult<'tcx, (Vec<u8>, Vec<(Size, AllocId)>)> {
+        match *self {
+            ConstValue::ScalarPair(a, b) => {
+                a.compatible_for_transmute(a_size, cx)?;
+                b.compatible_for_transmute(b_size, cx)?;
+                let (alloc, _) = self.force_to_byref(cx);
+                let relocations = alloc.relocations.iter().cloned().collect();
+                Ok((alloc.bytes, relocations))
+            }
+            ConstValue::Unevaluated(..) => err!(UnevaluatedConstant),
+            ConstValue::Scalar(..) |
+            ConstValue::ByRef(..) => err!(UnexpectedConstValueKind),
+        }
+    }
+
+    /// Dereferences a constant pointer, producing a `ByRef` for the memory it points to.
+    /// `alloc` has to be the allocation the pointer points into, which the caller has to look
+    /// up. Returns `None` if this is not a pointer.
+    #[inline]
+    pub fn deref(&self, alloc: &'tcx Allocation) -> Option<ConstValue<'tcx>> {
+        match *self {
+            ConstValue::Scalar(Scalar::Ptr(ptr)) => {
+                Some(ConstValue::ByRef(ptr.alloc_id, alloc, ptr.offset))
+            }
+            _ => None,
+        }
+    }
+
+    /// Like `try_to_scalar`, but for when anything else is a bug: ICEs with `msg` and the
+    /// offending value.
+    #[inline]
+    pub fn assert_scalar(&self, msg: &str) -> Scalar {
+        match *self {
+            ConstValue::Scalar(val) => val,
+            _ => bug!("{}: expected a scalar constant, got {:#?}", msg, self),
+        }
+    }
+
+    /// Reads the constant as a target `usize`, e.g. an array length. ICEs if it is anything but
+    /// an integer of the target's pointer size.
+    #[inline]
+    pub fn unwrap_usize(&self, cx: impl HasDataLayout) -> u64 {
+        let pointer_size = cx.data_layout().pointer_size;
+        match *self {
+            ConstValue::Scalar(Scalar::Bits { bits, size })
+                if size as u64 == pointer_size.bytes() => bits as u64,
+            _ => bug!("expected constant usize of {} bytes, got {:#?}", pointer_size.bytes(), self),
+        }
+    }
+
+    /// Wraps a scalar into a `ConstValue`, checking that it has the size of its type.
+    #[inline]
+    pub fn from_scalar_checked(val: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        match val {
+            Scalar::Bits { size: found, .. } if found as u64 != size.bytes() => {
+                err!(ScalarSizeMismatch { expected: size, found: Size::from_bytes(found as u64) })
+            }
+            _ => Ok(ConstValue::Scalar(val)),
+        }
+    }
+
+    /// Returns the ids of all allocations directly referenced by this value: those of pointer
+    /// scalars, and for `ByRef` the allocation itself and all of its relocations. Allocations
+    /// referenced by those allocations in turn are not included.
+    pub fn reachable_alloc_ids(&self) -> impl Iterator<Item = AllocId> + '_ {
+        let alloc_id = |val: Scalar| match val {
+            Scalar::Ptr(ptr) => Some(ptr.alloc_id),
+            Scalar::Bits { .. } => None,
+        };
+        let (a, b, relocations) = match *self {
+            ConstValue::Unevaluated(..) => (None, None, &[][..]),
+            ConstValue::Scalar(val) => (alloc_id(val), None, &[][..]),
+            ConstValue::ScalarPair(a, b) => (alloc_id(a), alloc_id(b), &[][..]),
+            ConstValue::ByRef(id, alloc, _) => {
+                (Some(id), None, alloc.relocations.iter().as_slice())
+            }
+        };
+        a.into_iter().chain(b).chain(relocations.iter().map(|&(_, id)| id))
+    }
+
+    /// Calls `visit` exactly once for every allocation reachable from this value, following
+    /// pointers stored in allocations transitively. `lookup` has to return the allocation for an
+    /// `AllocId`; allocations it returns `None` for are visited, but not followed any further.
+    /// Cycles, e.g. through self-referential statics, are fine.
+    pub fn visit_allocs(
+        &self,
+        mut lookup: impl FnMut(AllocId) -> Option<&'tcx Allocation>,
+        mut visit: impl FnMut(AllocId),
+    ) {
+        let mut seen = FxHashSet::default();
+        let mut todo: Vec<AllocId> = self.reachable_alloc_ids().collect();
+        while let Some(id) = todo.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            visit(id);
+            if let Some(alloc) = lookup(id) {
+                todo.extend(alloc.relocations.iter().map(|&(_, id)| id));
+            }
+        }
+    }
+
+    /// Like `try_to_bits`, but also reads integers stored in the memory of a `ByRef`, as long as
+    /// those bytes are defined and do not contain pointers.
+    pub fn try_to_bits_reading_memory(&self, size: Size, cx: impl HasDataLayout) -> Option<u128> {
+        match *self {
+            ConstValue::ByRef(_, alloc, offset) => {
+                if size.bytes() == 0 || size.bytes() > 16 {
+                    return None;
+                }
+                let end = offset.checked_add(size, cx)?;
+                if end.bytes() > alloc.bytes.len() as u64 {
+                    return None;
+                }
+                if !alloc.relocations_in(offset, size, cx).is_empty() {
+                    return None;
+                }
+                alloc.undef_mask.is_range_defined(offset, end).ok()?;
+                let bytes = &alloc.bytes[offset.bytes() as usize..end.bytes() as usize];
+                read_target_uint(cx.data_layout().endian, bytes).ok()
+            }
+            _ => self.try_to_bits(size),
+        }
+    }
+
+    /// Turns a `ByRef` of a type with `layout::abi::Scalar` ABI into the equivalent
+    /// `ConstValue::Scalar`, so that equal constants also compare and hash equal no matter
+    /// which representation they were created with. `abi` is the ABI of the value's type; all
+    /// values of other ABIs, which need memory backing, are returned unchanged, as are values
+    /// whose memory is undefined or holds a partial pointer.
+    pub fn canonicalize(self, abi: &layout::Abi, cx: impl HasDataLayout) -> Self {
+        let (alloc, offset) = match self {
+            ConstValue::ByRef(_, alloc, offset) => (alloc, offset),
+            _ => return self,
+        };
+        let primitive = match *abi {
+            layout::Abi::Scalar(ref scalar) => scalar.value,
+            _ => return self,
+        };
+        let size = primitive.size(cx);
+        if let Some(bits) = self.try_to_bits_reading_memory(size, cx) {
+            return ConstValue::Scalar(Scalar::from_uint(bits, size));
+        }
+        // See if we got a pointer
+        if let layout::Primitive::Pointer = primitive {
+            let end = match offset.checked_add(size, cx) {
+                Some(end) if end.bytes() <= alloc.bytes.len() as u64 => end,
+                _ => return self,
+            };
+            if alloc.undef_mask.is_range_defined(offset, end).is_err() {
+                return self;
+            }
+            if let Some(&alloc_id) = alloc.relocations.get(&offset) {
+                let bytes = &alloc.bytes[offset.bytes() as usize..end.bytes() as usize];
+                let bits = read_target_uint(cx.data_layout().endian, bytes).unwrap();
+                let ptr = Pointer::new(alloc_id, Size::from_bytes(bits as u64));
+                return ConstValue::Scalar(ptr.into());
+            }
+        }
+        self
+    }
 }
 
 impl<'tcx> Scalar {
+    #[inline]
+    pub fn null_of_size(size: Size) -> Self {
+        Scalar::Bits { bits: 0, size: size.bytes() as u8 }
+    }
+
+    #[inline]
+    pub fn zero(size: Size) -> Self {
+        Scalar::null_of_size(size)
+    }
+
+    /// The integer 1 of the given size. Zero-sized scalars cannot hold it, so `size` must not
+    /// be 0.
+    #[inline]
+    pub fn one(size: Size) -> Self {
+        assert_ne!(size.bytes(), 0, "a zero-sized scalar cannot be 1");
+        Scalar::Bits { bits: 1, size: size.bytes() as u8 }
+    }
+
     #[inline]
     pub fn ptr_null(cx: impl HasDataLayout) -> Self {
-        Scalar::Bits {
-            bits: 0,
-            size: cx.data_layout().pointer_size.bytes() as u8,
-        }
+        Scalar::null_of_size(cx.data_layout().pointer_size)
     }
 
     #[inline]
     pub fn zst() -> Self {
-        Scalar::Bits { bits: 0, size: 0 }
+        Scalar::null_of_size(Size::ZERO)
     }
 
+    /// Offsets a pointer by `i` bytes. If the pointer is just an integer address, overflowing
+    /// the address space is reported as `PointerArithOverflow`, so that it can be told apart from
+    /// other errors.
     #[inline]
     pub fn ptr_signed_offset(self, i: i64, cx: impl HasDataLayout) -> EvalResult<'tcx, Self> {
         let layout = cx.data_layout();
         match self {
             Scalar::Bits { bits, size } => {
                 assert_eq!(size as u64, layout.pointer_size.bytes());
-                Ok(Scalar::Bits {
-                    bits: layout.signed_offset(bits as u64, i)? as u128,
-                    size,
-                })
+                let (bits, over) = layout.overflowing_signed_offset(bits as u64, i as i128);
+                if over {
+                    return err!(PointerArithOverflow);
+                }
+                Ok(Scalar::Bits { bits: bits as u128, size })
             }
             Scalar::Ptr(ptr) => ptr.signed_offset(i, layout).map(Scalar::Ptr),
         }
@@ -123,6 +751,23 @@ impl<'tcx> Scalar {
         }
     }
 
+    /// Like `ptr_offset`, but wraps around on overflow and reports whether it did.
+    #[inline]
+    pub fn ptr_offset_overflowing(self, i: Size, cx: impl HasDataLayout) -> (Self, bool) {
+        let layout = cx.data_layout();
+        match self {
+            Scalar::Bits { bits, size } => {
+                assert_eq!(size as u64, layout.pointer_size.bytes());
+                let (res, over) = layout.overflowing_offset(bits as u64, i.bytes());
+                (Scalar::Bits { bits: res as u128, size }, over)
+            }
+            Scalar::Ptr(ptr) => {
+                let (ptr, over) = ptr.overflowing_offset(i, layout);
+                (Scalar::Ptr(ptr), over)
+            }
+        }
+    }
+
     #[inline]
     pub fn ptr_wrapping_signed_offset(self, i: i64, cx: impl HasDataLayout) -> Self {
         let layout = cx.data_layout();
@@ -157,9 +802,46 @@ impl<'tcx> Scalar {
         }
     }
 
+    /// Whether this is a `Bits` scalar of the given size holding exactly `bits`.
+    #[inline]
+    pub fn equals_bits(self, bits: u128, size: Size) -> bool {
+        match self {
+            Scalar::Bits { bits: b, size: s } => s as u64 == size.bytes() && b == bits,
+            Scalar::Ptr(_) => false,
+        }
+    }
+
+    /// Whether the sign bit of this `size`-wide integer is set. Errors on pointers.
+    #[inline]
+    pub fn is_negative(self, size: Size) -> EvalResult<'tcx, bool> {
+        assert_ne!(size.bytes(), 0, "zsts have no sign");
+        let bits = self.to_bits(size)?;
+        Ok((bits >> (size.bits() - 1)) & 1 == 1)
+    }
+
+    /// Whether this `size`-wide signed integer is strictly greater than zero.
+    #[inline]
+    pub fn is_positive(self, size: Size) -> EvalResult<'tcx, bool> {
+        Ok(!self.is_negative(size)? && !self.is_zero(size)?)
+    }
+
+    /// Whether this `size`-wide integer is zero. Errors on pointers, see `is_null` for
+    /// a variant that treats pointers as non-zero.
+    #[inline]
+    pub fn is_zero(self, size: Size) -> EvalResult<'tcx, bool> {
+        Ok(self.to_bits(size)? == 0)
+    }
+
     #[inline]
     pub fn from_bool(b: bool) -> Self {
-        Scalar::Bits { bits: b as u128, size: 1 }
+        Scalar::from_bool_sized(b, Size::from_bytes(1))
+    }
+
+    /// Creates a `bool` stored in a zero-padded slot of `size` bytes, see `to_bool_in`.
+    #[inline]
+    pub fn from_bool_sized(b: bool, size: Size) -> Self {
+        assert_ne!(size.bytes(), 0, "a bool cannot be stored in a zst");
+        Scalar::Bits { bits: b as u128, size: size.bytes() as u8 }
     }
 
     #[inline]
@@ -185,6 +867,47 @@ impl<'tcx> Scalar {
         Scalar::Bits { bits: truncated, size: size.bytes() as u8 }
     }
 
+    /// Like `from_uint`, but returns `None` if the value does not fit in `size`.
+    #[inline]
+    pub fn try_from_uint(i: impl Into<u128>, size: Size) -> Option<Self> {
+        let i = i.into();
+        // `truncate` cannot handle zsts, but only 0 fits in those anyway
+        if size.bytes() == 0 {
+            return if i == 0 { Some(Scalar::zst()) } else { None };
+        }
+        if truncate(i, size) == i {
+            Some(Scalar::Bits { bits: i, size: size.bytes() as u8 })
+        } else {
+            None
+        }
+    }
+
+    /// Like `from_int`, but returns `None` if the value does not fit in `size`.
+    #[inline]
+    pub fn try_from_int(i: impl Into<i128>, size: Size) -> Option<Self> {
+        let i = i.into();
+        if size.bytes() == 0 {
+            return if i == 0 { Some(Scalar::zst()) } else { None };
+        }
+        // `into` performed sign extension, we have to truncate
+        let truncated = truncate(i as u128, size);
+        if sign_extend(truncated, size) as i128 == i {
+            Some(Scalar::Bits { bits: truncated, size: size.bytes() as u8 })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn from_u16(i: u16) -> Self {
+        Scalar::from_uint(i, Size::from_bits(16))
+    }
+
+    #[inline]
+    pub fn from_i16(i: i16) -> Self {
+        Scalar::from_int(i, Size::from_bits(16))
+    }
+
     #[inline]
     pub fn from_f32(f: f32) -> Self {
         Scalar::Bits { bits: f.to_bits() as u128, size: 4 }
@@ -195,6 +918,33 @@ impl<'tcx> Scalar {
         Scalar::Bits { bits: f.to_bits() as u128, size: 8 }
     }
 
+    /// Creates an `f16` from its bit pattern. There is no arithmetic for `f16` yet, so the value
+    /// is only carried around as raw bits.
+    #[inline]
+    pub fn from_f16(bits: u16) -> Self {
+        Scalar::Bits { bits: bits as u128, size: 2 }
+    }
+
+    /// Creates an `f128` from its bit pattern, see `from_f16`.
+    #[inline]
+    pub fn from_f128(bits: u128) -> Self {
+        Scalar::Bits { bits, size: 16 }
+    }
+
+    /// Creates a scalar from raw bytes in the endianness of the target, the inverse of
+    /// `to_bytes`. The bytes must not contain (parts of) pointers, and there have to be 0, 1, 2,
+    /// 4, 8 or 16 of them, as there are no integers of any other size.
+    pub fn from_bytes(bytes: &[u8], cx: impl HasDataLayout) -> Self {
+        assert!(bytes.len() <= 16 && (bytes.is_empty() || bytes.len().is_power_of_two()),
+                "there are no scalars of {} bytes", bytes.len());
+        // `read_target_uint` cannot handle zero-sized sources
+        if bytes.is_empty() {
+            return Scalar::zst();
+        }
+        let bits = read_target_uint(cx.data_layout().endian, bytes).unwrap();
+        Scalar::Bits { bits, size: bytes.len() as u8 }
+    }
+
     #[inline]
     pub fn to_bits(self, target_size: Size) -> EvalResult<'tcx, u128> {
         match self {
@@ -207,6 +957,94 @@ impl<'tcx> Scalar {
         }
     }
 
+    /// Like `to_bits`, but does not require the scalar to have been created with `target_size`:
+    /// the bits are simply truncated to `target_size`. This loses the high bits if `target_size`
+    /// is smaller than the size of the scalar, so only use it for best-effort output like
+    /// diagnostics, never where the result matters for correctness.
+    #[inline]
+    pub fn to_bits_truncated(self, target_size: Size) -> EvalResult<'tcx, u128> {
+        assert_ne!(target_size.bytes(), 0, "to_bits_truncated cannot be used with zsts");
+        let (bits, _) = self.to_bits_any()?;
+        Ok(truncate(bits, target_size))
+    }
+
+    /// Like `to_bits`, but additionally errors with `InvalidNonZero` if the value is zero.
+    /// Pointers are never null, so they are treated exactly as `to_bits` treats them.
+    #[inline]
+    pub fn to_nonzero_bits(self, target_size: Size) -> EvalResult<'tcx, u128> {
+        match self.to_bits(target_size)? {
+            0 => err!(InvalidNonZero),
+            bits => Ok(bits),
+        }
+    }
+
+    /// Like `to_bits`, but for when the size is not known up front: returns the bits together
+    /// with the size the scalar was created with.
+    #[inline]
+    pub fn to_bits_any(self) -> EvalResult<'static, (u128, Size)> {
+        match self {
+            Scalar::Bits { bits, size } => Ok((bits, Size::from_bytes(size as u64))),
+            Scalar::Ptr(_) => err!(ReadPointerAsBytes),
+        }
+    }
+
+    /// Like `to_bits_any`, but returns the size as the raw byte count stored in the scalar.
+    #[inline]
+    pub fn to_bits_with_size(self) -> EvalResult<'tcx, (u128, u8)> {
+        let (bits, size) = self.to_bits_any()?;
+        Ok((bits, size.bytes() as u8))
+    }
+
+    /// Interprets the bits as a signed integer of the width the scalar was created with.
+    /// Errors on pointers.
+    #[inline]
+    pub fn sign_extended(self) -> EvalResult<'tcx, i128> {
+        let (bits, size) = self.to_bits_any()?;
+        if size.bytes() == 0 {
+            return Ok(0);
+        }
+        Ok(sign_extend(bits, size) as i128)
+    }
+
+    /// Interprets the bits as an unsigned integer of the width the scalar was created with.
+    /// Errors on pointers.
+    #[inline]
+    pub fn zero_extended(self) -> EvalResult<'tcx, u128> {
+        Ok(self.to_bits_any()?.0)
+    }
+
+    /// Interprets the value as an integer of `size` that is signed if `signed` is set. Unsigned
+    /// values are zero extended, so a `u128` that does not fit into an `i128` becomes negative.
+    #[inline]
+    pub fn to_int(self, size: Size, signed: bool) -> EvalResult<'tcx, i128> {
+        let bits = self.to_bits(size)?;
+        if signed {
+            Ok(sign_extend(bits, size) as i128)
+        } else {
+            Ok(bits as i128)
+        }
+    }
+
+    /// Returns the bits of a `Bits` scalar, or the pointer of a `Ptr` scalar as the `Err` variant.
+    #[inline]
+    pub fn to_bits_or_ptr(
+        self,
+        target_size: Size,
+        cx: impl HasDataLayout,
+    ) -> Result<u128, Pointer> {
+        match self {
+            Scalar::Bits { bits, size } => {
+                assert_eq!(target_size.bytes(), size as u64);
+                assert_ne!(size, 0, "to_bits_or_ptr cannot be used with zsts");
+                Ok(bits)
+            }
+            Scalar::Ptr(ptr) => {
+                assert_eq!(target_size, cx.data_layout().pointer_size);
+                Err(ptr)
+            }
+        }
+    }
+
     #[inline]
     pub fn to_ptr(self) -> EvalResult<'tcx, Pointer> {
         match self {
@@ -216,6 +1054,53 @@ impl<'tcx> Scalar {
         }
     }
 
+    /// Like `to_ptr`, but for when anything other than a pointer is a bug: ICEs with `msg` and
+    /// the offending scalar.
+    #[inline]
+    pub fn expect_ptr(self, msg: &str) -> Pointer {
+        match self {
+            Scalar::Ptr(p) => p,
+            Scalar::Bits { .. } => bug!("{}: expected a pointer, got {:?}", msg, self),
+        }
+    }
+
+    /// Formats an integer in hexadecimal, zero-padded to its size, e.g. `0x00ff` for a `u16`.
+    /// Returns `None` for pointers.
+    pub fn fmt_hex(self) -> Option<String> {
+        match self {
+            Scalar::Bits { bits, size } => {
+                Some(format!("0x{:0width$x}", bits, width = size as usize * 2))
+            }
+            Scalar::Ptr(_) => None,
+        }
+    }
+
+    /// Describes the value in words for use in error messages, e.g. "the integer 255" or
+    /// "a pointer into allocation 3 at offset 4". Without type information, a pointer-sized 0
+    /// is assumed to be a null pointer.
+    pub fn describe(self, cx: impl HasDataLayout) -> String {
+        match self {
+            Scalar::Bits { size: 0, .. } => "a zero-sized value".to_string(),
+            Scalar::Bits { bits: 0, size }
+                if size as u64 == cx.data_layout().pointer_size.bytes() =>
+            {
+                "a null pointer".to_string()
+            }
+            Scalar::Bits { bits, .. } => format!("the integer {}", bits),
+            Scalar::Ptr(ptr) => format!(
+                "a pointer into allocation {} at offset {}",
+                ptr.alloc_id,
+                ptr.offset.bytes(),
+            ),
+        }
+    }
+
+    /// The offset of a pointer within its allocation. Errors like `to_ptr` for `Bits`.
+    #[inline]
+    pub fn ptr_offset_bytes(self) -> EvalResult<'tcx, Size> {
+        Ok(self.to_ptr()?.offset)
+    }
+
     #[inline]
     pub fn is_bits(self) -> bool {
         match self {
@@ -232,14 +1117,43 @@ impl<'tcx> Scalar {
         }
     }
 
+    /// Reads a `bool` in its canonical representation: a single byte that is either 0 or 1.
     pub fn to_bool(self) -> EvalResult<'tcx, bool> {
+        self.to_bool_in(Size::from_bytes(1))
+    }
+
+    /// Reads a `bool` stored in a zero-padded slot of `size` bytes, e.g. an enum discriminant.
+    /// The value still has to be exactly 0 or 1.
+    pub fn to_bool_in(self, size: Size) -> EvalResult<'tcx, bool> {
         match self {
-            Scalar::Bits { bits: 0, size: 1 } => Ok(false),
-            Scalar::Bits { bits: 1, size: 1 } => Ok(true),
+            Scalar::Bits { bits: 0, size: s } if s != 0 && s as u64 == size.bytes() => Ok(false),
+            Scalar::Bits { bits: 1, size: s } if s != 0 && s as u64 == size.bytes() => Ok(true),
             _ => err!(InvalidBool),
         }
     }
 
+    /// Whether the value lies in the inclusive range `start..=end`. If `start > end`, the range
+    /// wraps around the maximum value: it then contains everything from `start` upwards and
+    /// everything from 0 up to `end`. This matches how `layout::Scalar::valid_range` is used.
+    pub fn in_range(self, start: u128, end: u128, size: Size) -> EvalResult<'tcx, bool> {
+        let bits = self.to_bits(size)?;
+        Ok(if start <= end {
+            start <= bits && bits <= end
+        } else {
+            start <= bits || bits <= end
+        })
+    }
+
+    /// Like `to_bool`, but accepts any nonzero value of any size as `true`. Only use this where
+    /// nothing more than zero/nonzero is guaranteed, e.g. for booleans coming from FFI.
+    pub fn to_bool_lenient(self) -> EvalResult<'tcx, bool> {
+        match self {
+            Scalar::Bits { size: 0, .. } => err!(InvalidBool),
+            Scalar::Bits { bits, .. } => Ok(bits != 0),
+            Scalar::Ptr(_) => err!(InvalidBool),
+        }
+    }
+
     pub fn to_char(self) -> EvalResult<'tcx, char> {
         let val = self.to_u32()?;
         match ::std::char::from_u32(val) {
@@ -248,6 +1162,29 @@ impl<'tcx> Scalar {
         }
     }
 
+    /// Like `to_char`, but returns `None` instead of erroring, and also for scalars that are not
+    /// four bytes wide. Meant for diagnostics that show a value as `char` if they can.
+    #[inline]
+    pub fn try_as_char(self) -> Option<char> {
+        match self {
+            Scalar::Bits { bits, size: 4 } => ::std::char::from_u32(bits as u32),
+            _ => None,
+        }
+    }
+
+    /// Like `to_char`, but maps invalid code points, and integers that do not have the 4 bytes
+    /// of a `char`, to U+FFFD REPLACEMENT CHARACTER instead of erroring. Only meant for
+    /// displaying values, e.g. in diagnostics.
+    pub fn to_char_lossy(self) -> EvalResult<'static, char> {
+        if let Scalar::Bits { size, .. } = self {
+            if size != 4 {
+                return Ok(::std::char::REPLACEMENT_CHARACTER);
+            }
+        }
+        let val = self.to_u32()?;
+        Ok(::std::char::from_u32(val).unwrap_or(::std::char::REPLACEMENT_CHARACTER))
+    }
+
     pub fn to_u8(self) -> EvalResult<'static, u8> {
         let sz = Size::from_bits(8);
         let b = self.to_bits(sz)?;
@@ -255,6 +1192,13 @@ impl<'tcx> Scalar {
         Ok(b as u8)
     }
 
+    pub fn to_u16(self) -> EvalResult<'static, u16> {
+        let sz = Size::from_bits(16);
+        let b = self.to_bits(sz)?;
+        assert_eq!(b as u16 as u128, b);
+        Ok(b as u16)
+    }
+
     pub fn to_u32(self) -> EvalResult<'static, u32> {
         let sz = Size::from_bits(32);
         let b = self.to_bits(sz)?;
@@ -269,8 +1213,30 @@ impl<'tcx> Scalar {
         Ok(b as u64)
     }
 
+    pub fn to_u128(self) -> EvalResult<'static, u128> {
+        let sz = Size::from_bits(128);
+        self.to_bits(sz)
+    }
+
+    /// Makes sure a `Bits` scalar has the size of a pointer on the target, so that reading a
+    /// `usize` or `isize` of the wrong width is reported instead of failing an assertion in
+    /// `to_bits`.
+    fn check_pointer_size(self, cx: impl HasDataLayout) -> EvalResult<'static, Size> {
+        let pointer_size = cx.data_layout().pointer_size;
+        match self {
+            Scalar::Bits { size, .. } if size as u64 != pointer_size.bytes() => {
+                err!(ScalarSizeMismatch {
+                    expected: pointer_size,
+                    found: Size::from_bytes(size as u64),
+                })
+            }
+            _ => Ok(pointer_size),
+        }
+    }
+
+    /// Reads a `usize`. Its width is that of a pointer on the *target*, which need not be 64 bit.
     pub fn to_usize(self, cx: impl HasDataLayout) -> EvalResult<'static, u64> {
-        let b = self.to_bits(cx.data_layout().pointer_size)?;
+        let b = self.to_bits(self.check_pointer_size(cx)?)?;
         assert_eq!(b as u64 as u128, b);
         Ok(b as u64)
     }
@@ -283,6 +1249,14 @@ impl<'tcx> Scalar {
         Ok(b as i8)
     }
 
+    pub fn to_i16(self) -> EvalResult<'static, i16> {
+        let sz = Size::from_bits(16);
+        let b = self.to_bits(sz)?;
+        let b = sign_extend(b, sz) as i128;
+        assert_eq!(b as i16 as i128, b);
+        Ok(b as i16)
+    }
+
     pub fn to_i32(self) -> EvalResult<'static, i32> {
         let sz = Size::from_bits(32);
         let b = self.to_bits(sz)?;
@@ -299,47 +1273,1663 @@ impl<'tcx> Scalar {
         Ok(b as i64)
     }
 
+    pub fn to_i128(self) -> EvalResult<'static, i128> {
+        let sz = Size::from_bits(128);
+        let b = self.to_bits(sz)?;
+        Ok(sign_extend(b, sz) as i128)
+    }
+
+    /// Reads an `isize`. Its width is that of a pointer on the *target*, which need not be 64 bit.
     pub fn to_isize(self, cx: impl HasDataLayout) -> EvalResult<'static, i64> {
-        let b = self.to_bits(cx.data_layout().pointer_size)?;
-        let b = sign_extend(b, cx.data_layout().pointer_size) as i128;
+        let pointer_size = self.check_pointer_size(cx)?;
+        let b = self.to_bits(pointer_size)?;
+        let b = sign_extend(b, pointer_size) as i128;
         assert_eq!(b as i64 as i128, b);
         Ok(b as i64)
     }
 
+    /// Alias for `to_usize` that spells out that the width depends on the target.
+    #[inline]
+    pub fn to_target_usize(self, cx: impl HasDataLayout) -> EvalResult<'static, u64> {
+        self.to_usize(cx)
+    }
+
+    /// Alias for `to_isize` that spells out that the width depends on the target.
+    #[inline]
+    pub fn to_target_isize(self, cx: impl HasDataLayout) -> EvalResult<'static, i64> {
+        self.to_isize(cx)
+    }
+
+    /// Makes sure a `Bits` scalar has the size of the float type it is about to be read as,
+    /// so that malformed constants are reported instead of failing an assertion in `to_bits`.
+    fn check_float_size(self, expected: Size) -> EvalResult<'static> {
+        match self {
+            Scalar::Bits { size, .. } if size as u64 != expected.bytes() => {
+                err!(InvalidFloatSize { expected, found: Size::from_bytes(size as u64) })
+            }
+            _ => Ok(()),
+        }
+    }
+
     #[inline]
     pub fn to_f32(self) -> EvalResult<'static, f32> {
+        self.check_float_size(Size::from_bits(32))?;
         Ok(f32::from_bits(self.to_u32()?))
     }
 
     #[inline]
     pub fn to_f64(self) -> EvalResult<'static, f64> {
+        self.check_float_size(Size::from_bits(64))?;
         Ok(f64::from_bits(self.to_u64()?))
     }
-}
 
-impl From<Pointer> for Scalar {
-    #[inline(always)]
-    fn from(ptr: Pointer) -> Self {
-        Scalar::Ptr(ptr)
+    /// Returns the bit pattern of an `f16`, see `from_f16`.
+    #[inline]
+    pub fn to_f16(self) -> EvalResult<'static, u16> {
+        self.check_float_size(Size::from_bits(16))?;
+        self.to_u16()
     }
-}
 
-/// A `Scalar` represents an immediate, primitive value existing outside of a
-/// `memory::Allocation`. It is in many ways like a small chunk of a `Allocation`, up to 8 bytes in
-/// size. Like a range of bytes in an `Allocation`, a `Scalar` can either represent the raw bytes
-/// of a simple value or a pointer into another `Allocation`
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, RustcEncodable, RustcDecodable, Hash)]
-pub enum Scalar<Id=AllocId> {
-    /// The raw bytes of a simple value.
-    Bits {
-        /// The first `size` bytes are the value.
-        /// Do not try to read less or more bytes that that. The remaining bytes must be 0.
-        size: u8,
-        bits: u128,
-    },
+    /// Returns the bit pattern of an `f128`, see `from_f16`.
+    #[inline]
+    pub fn to_f128(self) -> EvalResult<'static, u128> {
+        self.check_float_size(Size::from_bits(128))?;
+        self.to_u128()
+    }
 
-    /// A pointer into an `Allocation`. An `Allocation` in the `memory` module has a list of
-    /// relocations, but a `Scalar` is only large enough to contain one, so we just represent the
-    /// relocation and its associated offset together as a `Pointer` here.
-    Ptr(Pointer<Id>),
+    /// Converts an integer of `size` to `f32` like an `as` cast does, rounding to the nearest
+    /// representable value, ties to even. Values too large for `f32` become infinity.
+    pub fn int_to_f32(self, size: Size, signed: bool) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?;
+        let f = if signed {
+            Single::from_i128(sign_extend(bits, size) as i128).value
+        } else {
+            Single::from_u128(bits).value
+        };
+        Ok(Scalar::from_uint(f.to_bits(), Size::from_bits(32)))
+    }
+
+    /// Converts an integer of `size` to `f64` like an `as` cast does, see `int_to_f32`.
+    pub fn int_to_f64(self, size: Size, signed: bool) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?;
+        let f = if signed {
+            Double::from_i128(sign_extend(bits, size) as i128).value
+        } else {
+            Double::from_u128(bits).value
+        };
+        Ok(Scalar::from_uint(f.to_bits(), Size::from_bits(64)))
+    }
+
+    /// Compares two `f32` values following IEEE 754, so the result is `None` if either one is
+    /// NaN, and `0.0` and `-0.0` are equal.
+    pub fn float_cmp_f32(self, other: Scalar) -> EvalResult<'tcx, Option<Ordering>> {
+        let size = Size::from_bits(32);
+        self.check_float_size(size)?;
+        other.check_float_size(size)?;
+        let l = Single::from_bits(self.to_bits(size)?);
+        let r = Single::from_bits(other.to_bits(size)?);
+        Ok(l.partial_cmp(&r))
+    }
+
+    /// Compares two `f64` values following IEEE 754, see `float_cmp_f32`.
+    pub fn float_cmp_f64(self, other: Scalar) -> EvalResult<'tcx, Option<Ordering>> {
+        let size = Size::from_bits(64);
+        self.check_float_size(size)?;
+        other.check_float_size(size)?;
+        let l = Double::from_bits(self.to_bits(size)?);
+        let r = Double::from_bits(other.to_bits(size)?);
+        Ok(l.partial_cmp(&r))
+    }
+
+    /// Compares two integers of `size` numerically, interpreting them as unsigned. Unlike the
+    /// derived `Ord`, which compares the representation, this errors for pointers.
+    #[inline]
+    pub fn cmp_unsigned(self, other: Scalar, size: Size) -> EvalResult<'tcx, Ordering> {
+        Ok(self.to_bits(size)?.cmp(&other.to_bits(size)?))
+    }
+
+    /// Compares two integers of `size` numerically, interpreting them as signed.
+    #[inline]
+    pub fn cmp_signed(self, other: Scalar, size: Size) -> EvalResult<'tcx, Ordering> {
+        let l = sign_extend(self.to_bits(size)?, size) as i128;
+        let r = sign_extend(other.to_bits(size)?, size) as i128;
+        Ok(l.cmp(&r))
+    }
+
+    #[inline]
+    pub fn min_unsigned(self, other: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(if self.cmp_unsigned(other, size)? != Ordering::Greater { self } else { other })
+    }
+
+    #[inline]
+    pub fn max_unsigned(self, other: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(if self.cmp_unsigned(other, size)? != Ordering::Less { self } else { other })
+    }
+
+    #[inline]
+    pub fn min_signed(self, other: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(if self.cmp_signed(other, size)? != Ordering::Greater { self } else { other })
+    }
+
+    #[inline]
+    pub fn max_signed(self, other: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(if self.cmp_signed(other, size)? != Ordering::Less { self } else { other })
+    }
+
+    /// Restricts an unsigned integer of `size` to the range `lo..=hi`, like `Ord::clamp`.
+    /// Errors if `lo > hi`, where `clamp` would panic.
+    pub fn clamp_unsigned(self, lo: Scalar, hi: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        if lo.cmp_unsigned(hi, size)? == Ordering::Greater {
+            return err!(InvalidClampRange);
+        }
+        self.max_unsigned(lo, size)?.min_unsigned(hi, size)
+    }
+
+    /// Like `clamp_unsigned`, but interprets all three operands as signed integers of `size`.
+    pub fn clamp_signed(self, lo: Scalar, hi: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        if lo.cmp_signed(hi, size)? == Ordering::Greater {
+            return err!(InvalidClampRange);
+        }
+        self.max_signed(lo, size)?.min_signed(hi, size)
+    }
+
+    /// IEEE 754 `minNum` of two `f64`s: if exactly one of them is NaN, the other one is
+    /// returned.
+    pub fn min_f64(self, other: Scalar) -> EvalResult<'tcx, Self> {
+        let size = Size::from_bits(64);
+        self.check_float_size(size)?;
+        other.check_float_size(size)?;
+        let l = Double::from_bits(self.to_bits(size)?);
+        let r = Double::from_bits(other.to_bits(size)?);
+        Ok(if l.min(r).bitwise_eq(l) { self } else { other })
+    }
+
+    /// IEEE 754 `maxNum` of two `f64`s: if exactly one of them is NaN, the other one is
+    /// returned.
+    pub fn max_f64(self, other: Scalar) -> EvalResult<'tcx, Self> {
+        let size = Size::from_bits(64);
+        self.check_float_size(size)?;
+        other.check_float_size(size)?;
+        let l = Double::from_bits(self.to_bits(size)?);
+        let r = Double::from_bits(other.to_bits(size)?);
+        Ok(if l.max(r).bitwise_eq(l) { self } else { other })
+    }
+
+    /// Restricts an `f64` to the range `lo..=hi`, like `f64::clamp`. A NaN value is returned
+    /// unchanged. Errors if `lo > hi` or either bound is NaN, where `clamp` would panic.
+    pub fn clamp_f64(self, lo: Scalar, hi: Scalar) -> EvalResult<'tcx, Self> {
+        match lo.float_cmp_f64(hi)? {
+            Some(Ordering::Less) | Some(Ordering::Equal) => {}
+            Some(Ordering::Greater) | None => return err!(InvalidClampRange),
+        }
+        Ok(match (self.float_cmp_f64(lo)?, self.float_cmp_f64(hi)?) {
+            (Some(Ordering::Less), _) => lo,
+            (_, Some(Ordering::Greater)) => hi,
+            _ => self,
+        })
+    }
+
+    /// `1.0` with the sign of `f`, or NaN if `f` is NaN. Like `f64::signum`, this returns
+    /// `-1.0` for `-0.0`.
+    fn float_signum<F: Float>(f: F) -> F {
+        if f.is_nan() {
+            return f;
+        }
+        let one = F::from_u128(1).value;
+        if f.is_negative() { -one } else { one }
+    }
+
+    /// The `f32` version of `signum_f64`.
+    pub fn signum_f32(self) -> EvalResult<'tcx, Self> {
+        let size = Size::from_bits(32);
+        self.check_float_size(size)?;
+        let f = Scalar::float_signum(Single::from_bits(self.to_bits(size)?));
+        Ok(Scalar::from_uint(f.to_bits(), size))
+    }
+
+    /// Returns `1.0` with the sign of the value, so `-1.0` for all negative values, including
+    /// `-0.0`. NaN is returned unchanged.
+    pub fn signum_f64(self) -> EvalResult<'tcx, Self> {
+        let size = Size::from_bits(64);
+        self.check_float_size(size)?;
+        let f = Scalar::float_signum(Double::from_bits(self.to_bits(size)?));
+        Ok(Scalar::from_uint(f.to_bits(), size))
+    }
+
+    /// Combines the magnitude of the float `self` of `size` with the sign bit of `sign`. This
+    /// works on the bits directly, so NaNs keep their payload and the sign of zero is honored.
+    fn copysign(self, sign: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        self.check_float_size(size)?;
+        sign.check_float_size(size)?;
+        let sign_bit = 1 << (size.bits() - 1);
+        let bits = (self.to_bits(size)? & !sign_bit) | (sign.to_bits(size)? & sign_bit);
+        Ok(Scalar::from_uint(bits, size))
+    }
+
+    /// The `f32` version of `copysign_f64`.
+    #[inline]
+    pub fn copysign_f32(self, sign: Scalar) -> EvalResult<'tcx, Self> {
+        self.copysign(sign, Size::from_bits(32))
+    }
+
+    /// Returns the value with the sign of `sign`, like `f64::copysign`.
+    #[inline]
+    pub fn copysign_f64(self, sign: Scalar) -> EvalResult<'tcx, Self> {
+        self.copysign(sign, Size::from_bits(64))
+    }
+
+    fn float_to_int<F: Float>(f: F, size: Size, signed: bool) -> Self {
+        let width = size.bits() as usize;
+        if signed {
+            Scalar::from_int(f.to_i128(width).value, size)
+        } else {
+            Scalar::from_uint(f.to_u128(width).value, size)
+        }
+    }
+
+    /// Converts an `f32` to an integer of `size` like an `as` cast does: the value is rounded
+    /// towards zero and saturates at the bounds of the integer type, NaN becomes 0.
+    pub fn f32_to_int(self, size: Size, signed: bool) -> EvalResult<'tcx, Self> {
+        self.check_float_size(Size::from_bits(32))?;
+        let bits = self.to_bits(Size::from_bits(32))?;
+        Ok(Scalar::float_to_int(Single::from_bits(bits), size, signed))
+    }
+
+    /// Converts an `f64` to an integer of `size` like an `as` cast does, see `f32_to_int`.
+    pub fn f64_to_int(self, size: Size, signed: bool) -> EvalResult<'tcx, Self> {
+        self.check_float_size(Size::from_bits(64))?;
+        let bits = self.to_bits(Size::from_bits(64))?;
+        Ok(Scalar::float_to_int(Double::from_bits(bits), size, signed))
+    }
+
+    /// Applies an overflowing operation on unsigned integers to the bits of both operands and
+    /// returns the result truncated to `size`, together with whether it overflowed.
+    fn overflowing_uint_op(
+        self,
+        rhs: Scalar,
+        size: Size,
+        op: fn(u128, u128) -> (u128, bool),
+    ) -> EvalResult<'tcx, (Scalar, bool)> {
+        let l = self.to_bits(size)?;
+        let r = rhs.to_bits(size)?;
+        let (result, oflo) = op(l, r);
+        let truncated = truncate(result, size);
+        Ok((Scalar::from_uint(truncated, size), oflo || truncated != result))
+    }
+
+    /// Like `overflowing_uint_op`, but interprets both operands as signed integers of `size`.
+    fn overflowing_int_op(
+        self,
+        rhs: Scalar,
+        size: Size,
+        op: fn(i128, i128) -> (i128, bool),
+    ) -> EvalResult<'tcx, (Scalar, bool)> {
+        let l = sign_extend(self.to_bits(size)?, size) as i128;
+        let r = sign_extend(rhs.to_bits(size)?, size) as i128;
+        let (result, mut oflo) = op(l, r);
+        if !oflo && size.bits() != 128 {
+            let max = 1 << (size.bits() - 1);
+            oflo = result >= max || result < -max;
+        }
+        // this may be out-of-bounds for the result type, so we have to truncate ourselves
+        let truncated = truncate(result as u128, size);
+        Ok((Scalar::from_uint(truncated, size), oflo))
+    }
+
+    #[inline]
+    pub fn checked_add_uint(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, (Scalar, bool)> {
+        self.overflowing_uint_op(rhs, size, u128::overflowing_add)
+    }
+
+    #[inline]
+    pub fn checked_sub_uint(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, (Scalar, bool)> {
+        self.overflowing_uint_op(rhs, size, u128::overflowing_sub)
+    }
+
+    #[inline]
+    pub fn checked_mul_uint(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, (Scalar, bool)> {
+        self.overflowing_uint_op(rhs, size, u128::overflowing_mul)
+    }
+
+    #[inline]
+    pub fn checked_add_int(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, (Scalar, bool)> {
+        self.overflowing_int_op(rhs, size, i128::overflowing_add)
+    }
+
+    #[inline]
+    pub fn checked_sub_int(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, (Scalar, bool)> {
+        self.overflowing_int_op(rhs, size, i128::overflowing_sub)
+    }
+
+    #[inline]
+    pub fn checked_mul_int(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, (Scalar, bool)> {
+        self.overflowing_int_op(rhs, size, i128::overflowing_mul)
+    }
+
+    /// Adds two integers of `size` modulo `2^size.bits()`. Since the result is the same for
+    /// signed and unsigned integers, there is only one version of this.
+    #[inline]
+    pub fn wrapping_add(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(self.checked_add_uint(rhs, size)?.0)
+    }
+
+    /// Subtracts two integers of `size` modulo `2^size.bits()`, see `wrapping_add`.
+    #[inline]
+    pub fn wrapping_sub(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(self.checked_sub_uint(rhs, size)?.0)
+    }
+
+    /// Multiplies two integers of `size` modulo `2^size.bits()`, see `wrapping_add`.
+    #[inline]
+    pub fn wrapping_mul(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(self.checked_mul_uint(rhs, size)?.0)
+    }
+
+    /// Adds two unsigned integers of `size`, clamping the result to the maximum of `size`.
+    pub fn saturating_add_unsigned(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        match self.checked_add_uint(rhs, size)? {
+            (_, true) => Ok(Scalar::from_uint(bitmask(size), size)),
+            (result, false) => Ok(result),
+        }
+    }
+
+    /// Subtracts two unsigned integers of `size`, clamping the result to zero.
+    pub fn saturating_sub_unsigned(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        match self.checked_sub_uint(rhs, size)? {
+            (_, true) => Ok(Scalar::from_uint(0u128, size)),
+            (result, false) => Ok(result),
+        }
+    }
+
+    /// Adds two signed integers of `size`, clamping the result to the range of `size`.
+    pub fn saturating_add_signed(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        match self.checked_add_int(rhs, size)? {
+            // the sum can only overflow towards the side of `rhs`
+            (_, true) => Ok(Scalar::signed_bound(size, !rhs.is_negative(size)?)),
+            (result, false) => Ok(result),
+        }
+    }
+
+    /// Subtracts two signed integers of `size`, clamping the result to the range of `size`.
+    pub fn saturating_sub_signed(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        match self.checked_sub_int(rhs, size)? {
+            // the difference can only overflow away from the side of `rhs`
+            (_, true) => Ok(Scalar::signed_bound(size, rhs.is_negative(size)?)),
+            (result, false) => Ok(result),
+        }
+    }
+
+    /// The largest (if `max`) or smallest signed integer of `size`.
+    fn signed_bound(size: Size, max: bool) -> Self {
+        let max_bits = bitmask(size) >> 1;
+        let bits = if max { max_bits } else { max_bits + 1 };
+        Scalar::Bits { bits, size: size.bytes() as u8 }
+    }
+
+    /// Raises an unsigned integer to the power `exp` by squaring, truncating to `size` after
+    /// every step. Like `u32::overflowing_pow`, returns the wrapped result and whether any of
+    /// the multiplications actually needed for it overflowed.
+    pub fn checked_pow_unsigned(self, mut exp: u32, size: Size) -> EvalResult<'tcx, (Self, bool)> {
+        let mask = bitmask(size);
+        let mul = |l: u128, r: u128| {
+            let (result, oflo) = l.overflowing_mul(r);
+            (result & mask, oflo || result > mask)
+        };
+        let mut base = self.to_bits(size)?;
+        let mut acc = 1;
+        let mut overflowed = false;
+        while exp > 1 {
+            if exp & 1 == 1 {
+                let (result, oflo) = mul(acc, base);
+                acc = result;
+                overflowed |= oflo;
+            }
+            exp /= 2;
+            let (result, oflo) = mul(base, base);
+            base = result;
+            overflowed |= oflo;
+        }
+        if exp == 1 {
+            let (result, oflo) = mul(acc, base);
+            acc = result;
+            overflowed |= oflo;
+        }
+        Ok((Scalar::from_uint(acc, size), overflowed))
+    }
+
+    /// Raises an `f64` to the integer power `exp` the way `llvm.powi` does at runtime: by
+    /// squaring with IEEE rounding after every multiplication, and taking the reciprocal at the
+    /// end for negative exponents. This only uses soft floats, so it is the same on all hosts.
+    pub fn powi_f64(self, exp: i32) -> EvalResult<'tcx, Self> {
+        let size = Size::from_bits(64);
+        self.check_float_size(size)?;
+        let one = Double::from_u128(1).value;
+        let mut base = Double::from_bits(self.to_bits(size)?);
+        let mut acc = one;
+        let recip = exp < 0;
+        let mut exp = exp;
+        loop {
+            if exp & 1 != 0 {
+                acc = (acc * base).value;
+            }
+            exp /= 2;
+            if exp == 0 {
+                break;
+            }
+            base = (base * base).value;
+        }
+        if recip {
+            acc = (one / acc).value;
+        }
+        Ok(Scalar::from_uint(acc.to_bits(), size))
+    }
+
+    /// Raises an `f64` to the power of another `f64`. Only integral exponents that fit in an
+    /// `i32` are supported, through `powi_f64`: anything else would need the host's `powf`,
+    /// whose result is not the same on all hosts.
+    pub fn powf_f64(self, exp: Scalar) -> EvalResult<'tcx, Self> {
+        let size = Size::from_bits(64);
+        exp.check_float_size(size)?;
+        let exp = Double::from_bits(exp.to_bits(size)?);
+        // `to_i128` saturates, so this also rejects NaN, infinities and all too large values
+        let int_exp = exp.to_i128(32).value;
+        if Double::from_i128(int_exp).value != exp {
+            return err!(Unimplemented(format!("powf with the exponent {}", exp)));
+        }
+        self.powi_f64(int_exp as i32)
+    }
+
+    /// Unsigned division, erroring on division by zero.
+    pub fn checked_div_unsigned(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        let l = self.to_bits(size)?;
+        let r = rhs.to_bits(size)?;
+        if r == 0 {
+            return err!(DivisionByZero);
+        }
+        Ok(Scalar::from_uint(l / r, size))
+    }
+
+    /// Unsigned remainder, erroring on division by zero.
+    pub fn checked_rem_unsigned(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        let l = self.to_bits(size)?;
+        let r = rhs.to_bits(size)?;
+        if r == 0 {
+            return err!(RemainderByZero);
+        }
+        Ok(Scalar::from_uint(l % r, size))
+    }
+
+    /// Signed division, erroring on division by zero and on `MIN / -1`, which overflows.
+    pub fn checked_div_signed(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        let (l, r) = self.signed_div_operands(rhs, size, mir::BinOp::Div)?;
+        Ok(Scalar::from_uint(truncate(l.wrapping_div(r) as u128, size), size))
+    }
+
+    /// Signed remainder, erroring on division by zero and on `MIN % -1`, which overflows.
+    pub fn checked_rem_signed(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        let (l, r) = self.signed_div_operands(rhs, size, mir::BinOp::Rem)?;
+        Ok(Scalar::from_uint(truncate(l.wrapping_rem(r) as u128, size), size))
+    }
+
+    /// Sign extends both operands of a signed division or remainder, and checks for the two
+    /// cases in which those trap.
+    fn signed_div_operands(
+        self,
+        rhs: Scalar,
+        size: Size,
+        op: mir::BinOp,
+    ) -> EvalResult<'tcx, (i128, i128)> {
+        let l = sign_extend(self.to_bits(size)?, size) as i128;
+        let r = sign_extend(rhs.to_bits(size)?, size) as i128;
+        if r == 0 {
+            return match op {
+                mir::BinOp::Rem => err!(RemainderByZero),
+                _ => err!(DivisionByZero),
+            };
+        }
+        // `MIN / -1` does not fit into `size`
+        let min = sign_extend(1 << (size.bits() - 1), size) as i128;
+        if r == -1 && l == min {
+            return err!(Overflow(op));
+        }
+        Ok((l, r))
+    }
+
+    fn bitwise_op(
+        self,
+        rhs: Scalar,
+        size: Size,
+        op: fn(u128, u128) -> u128,
+    ) -> EvalResult<'tcx, Self> {
+        let l = self.to_bits(size)?;
+        let r = rhs.to_bits(size)?;
+        Ok(Scalar::from_uint(truncate(op(l, r), size), size))
+    }
+
+    #[inline]
+    pub fn bitand(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        self.bitwise_op(rhs, size, |l, r| l & r)
+    }
+
+    #[inline]
+    pub fn bitor(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        self.bitwise_op(rhs, size, |l, r| l | r)
+    }
+
+    #[inline]
+    pub fn bitxor(self, rhs: Scalar, size: Size) -> EvalResult<'tcx, Self> {
+        self.bitwise_op(rhs, size, |l, r| l ^ r)
+    }
+
+    #[inline]
+    pub fn not(self, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(Scalar::from_uint(!self.to_bits(size)? & bitmask(size), size))
+    }
+
+    /// Two's complement negation, wrapping around at the minimum value of `size`.
+    #[inline]
+    pub fn neg(self, size: Size) -> EvalResult<'tcx, Self> {
+        Ok(self.checked_neg(size)?.0)
+    }
+
+    /// Like `neg`, but also returns whether negation overflowed, which only happens for the
+    /// minimum signed value of `size`.
+    pub fn checked_neg(self, size: Size) -> EvalResult<'tcx, (Self, bool)> {
+        let bits = self.to_bits(size)?;
+        let min = 1u128 << (size.bits() - 1);
+        let result = truncate(0u128.wrapping_sub(bits), size);
+        Ok((Scalar::from_uint(result, size), bits == min))
+    }
+
+    /// The absolute value of the value interpreted as a signed integer of `size`, where the
+    /// minimum value stays unchanged like it does for `i32::wrapping_abs`.
+    pub fn wrapping_abs(self, size: Size) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?;
+        if (sign_extend(bits, size) as i128) < 0 {
+            self.neg(size)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// `-1`, `0` or `1` of `size`, depending on the sign of the value interpreted as a signed
+    /// integer of `size`, like `i32::signum`.
+    pub fn signum_signed(self, size: Size) -> EvalResult<'tcx, Self> {
+        let signum = if self.is_negative(size)? {
+            -1
+        } else if self.is_zero(size)? {
+            0
+        } else {
+            1
+        };
+        Ok(Scalar::from_int(signum, size))
+    }
+
+    /// Shifts the bits of the value left by `amount`, erroring if `amount` is not smaller than
+    /// the bit width of `size`.
+    pub fn shl(self, amount: u32, size: Size) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?;
+        if amount as u64 >= size.bits() {
+            return err!(Overflow(mir::BinOp::Shl));
+        }
+        Ok(Scalar::from_uint(truncate(bits << amount, size), size))
+    }
+
+    /// Logical right shift: the vacated high bits are filled with zeros.
+    pub fn shr_unsigned(self, amount: u32, size: Size) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?;
+        if amount as u64 >= size.bits() {
+            return err!(Overflow(mir::BinOp::Shr));
+        }
+        Ok(Scalar::from_uint(bits >> amount, size))
+    }
+
+    /// Arithmetic right shift: the vacated high bits are filled with the sign bit of the value
+    /// interpreted as a signed integer of `size`.
+    pub fn shr_signed(self, amount: u32, size: Size) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?;
+        if amount as u64 >= size.bits() {
+            return err!(Overflow(mir::BinOp::Shr));
+        }
+        let result = (sign_extend(bits, size) as i128) >> amount;
+        Ok(Scalar::from_uint(truncate(result as u128, size), size))
+    }
+
+    /// Decodes the amount of a checked shift on a value of `size`. Like `overflowing_shl`, the
+    /// amount is masked to the bit width of `size` and the flag reports whether it was too big.
+    /// `amount` can have any integer type, as the right hand side of MIR shifts can.
+    fn masked_shift_amount(amount: Scalar, size: Size) -> EvalResult<'tcx, (u32, bool)> {
+        assert_ne!(size.bytes(), 0, "zsts cannot be shifted");
+        let (amount, _) = amount.to_bits_any()?;
+        let width = size.bits() as u128;
+        Ok(((amount % width) as u32, amount >= width))
+    }
+
+    /// Shifts the bits of the value left by `amount` modulo the bit width of `size`. The flag
+    /// reports whether `amount` was out of range, in which case Rust code would panic.
+    pub fn checked_shl(self, amount: Scalar, size: Size) -> EvalResult<'tcx, (Self, bool)> {
+        let (amount, oflo) = Scalar::masked_shift_amount(amount, size)?;
+        Ok((self.shl(amount, size)?, oflo))
+    }
+
+    /// Like `checked_shl`, but for a logical right shift.
+    pub fn checked_shr_unsigned(
+        self,
+        amount: Scalar,
+        size: Size,
+    ) -> EvalResult<'tcx, (Self, bool)> {
+        let (amount, oflo) = Scalar::masked_shift_amount(amount, size)?;
+        Ok((self.shr_unsigned(amount, size)?, oflo))
+    }
+
+    /// Like `checked_shl`, but for an arithmetic right shift.
+    pub fn checked_shr_signed(
+        self,
+        amount: Scalar,
+        size: Size,
+    ) -> EvalResult<'tcx, (Self, bool)> {
+        let (amount, oflo) = Scalar::masked_shift_amount(amount, size)?;
+        Ok((self.shr_signed(amount, size)?, oflo))
+    }
+
+    /// Rotates the bits of the value left within the bit width of `size`. `amount` is taken
+    /// modulo that width.
+    pub fn rotate_left(self, amount: u32, size: Size) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?;
+        let width = size.bits() as u32;
+        let amount = amount % width;
+        if amount == 0 {
+            return Ok(self);
+        }
+        let rotated = (bits << amount) | (bits >> (width - amount));
+        Ok(Scalar::from_uint(rotated & bitmask(size), size))
+    }
+
+    /// Rotates the bits of the value right within the bit width of `size`. `amount` is taken
+    /// modulo that width.
+    pub fn rotate_right(self, amount: u32, size: Size) -> EvalResult<'tcx, Self> {
+        assert_ne!(size.bytes(), 0, "zsts cannot be rotated");
+        let width = size.bits() as u32;
+        self.rotate_left(width - amount % width, size)
+    }
+
+    #[inline]
+    pub fn count_ones(self, size: Size) -> EvalResult<'tcx, u32> {
+        Ok(self.to_bits(size)?.count_ones())
+    }
+
+    #[inline]
+    pub fn count_zeros(self, size: Size) -> EvalResult<'tcx, u32> {
+        Ok(size.bits() as u32 - self.count_ones(size)?)
+    }
+
+    /// The number of leading zeros within the bit width of `size`, which is that width for 0.
+    #[inline]
+    pub fn leading_zeros(self, size: Size) -> EvalResult<'tcx, u32> {
+        Ok(self.to_bits(size)?.leading_zeros() - bitmask(size).leading_zeros())
+    }
+
+    /// The number of trailing zeros within the bit width of `size`, which is that width for 0.
+    #[inline]
+    pub fn trailing_zeros(self, size: Size) -> EvalResult<'tcx, u32> {
+        // setting the bits above `size` stops the count at the bit width for 0
+        Ok((self.to_bits(size)? | !bitmask(size)).trailing_zeros())
+    }
+
+    /// Reverses the order of the `size` bytes making up the value.
+    #[inline]
+    pub fn swap_bytes(self, size: Size) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?.swap_bytes() >> bitmask(size).leading_zeros();
+        Ok(Scalar::from_uint(bits, size))
+    }
+
+    /// Reverses the order of the bits within the bit width of `size`.
+    #[inline]
+    pub fn reverse_bits(self, size: Size) -> EvalResult<'tcx, Self> {
+        let bits = self.to_bits(size)?.reverse_bits() >> bitmask(size).leading_zeros();
+        Ok(Scalar::from_uint(bits, size))
+    }
+
+    /// Truncates the value to `size`, dropping any bits that do not fit. `size` must not be
+    /// zero, as there is nothing left to truncate to.
+    #[inline]
+    pub fn truncate_to(self, size: Size) -> EvalResult<'tcx, Self> {
+        assert_ne!(size.bytes(), 0, "cannot truncate to a zst");
+        let (bits, _) = self.to_bits_any()?;
+        Ok(Scalar::Bits { bits: truncate(bits, size), size: size.bytes() as u8 })
+    }
+
+    /// Zero extends an integer to the pointer size of the target, e.g. to use it as an offset.
+    /// Pointers are returned unchanged. Errors if the integer is larger than a pointer.
+    pub fn zero_extend_to_ptr_size(self, cx: impl HasDataLayout) -> EvalResult<'tcx, Self> {
+        let pointer_size = cx.data_layout().pointer_size;
+        match self {
+            Scalar::Bits { bits, size } => {
+                if size as u64 > pointer_size.bytes() {
+                    return err!(ScalarSizeMismatch {
+                        expected: pointer_size,
+                        found: Size::from_bytes(size as u64),
+                    });
+                }
+                Ok(Scalar::Bits { bits, size: pointer_size.bytes() as u8 })
+            }
+            Scalar::Ptr(_) => Ok(self),
+        }
+    }
+
+    /// Checks whether this scalar can be reinterpreted as a value of `other_size` bytes, e.g. as
+    /// the result of a `transmute`. Integers have to keep their size, and a pointer can only be
+    /// read back as a whole, never split into or merged with smaller pieces.
+    pub fn compatible_for_transmute(
+        self,
+        other_size: Size,
+        cx: impl HasDataLayout,
+    ) -> EvalResult<'tcx, ()> {
+        match self {
+            Scalar::Bits { size, .. } if size as u64 != other_size.bytes() => {
+                err!(ScalarSizeMismatch {
+                    expected: other_size,
+                    found: Size::from_bytes(size as u64),
+                })
+            }
+            Scalar::Ptr(_) if other_size != cx.data_layout().pointer_size => {
+                err!(ReadPointerAsBytes)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Interprets the value as a signed integer of size `from` and sign extends it to `to`.
+    #[inline]
+    pub fn sign_extend_to(self, from: Size, to: Size) -> EvalResult<'tcx, Self> {
+        assert!(from <= to, "cannot sign extend from {} to {} bits", from.bits(), to.bits());
+        let bits = sign_extend(self.to_bits(from)?, from);
+        Ok(Scalar::Bits { bits: truncate(bits, to), size: to.bytes() as u8 })
+    }
+
+    /// The size of the scalar and the alignment an integer (or pointer) of that size has.
+    fn size_and_align(self, cx: impl HasDataLayout) -> (Size, Align) {
+        let dl = cx.data_layout();
+        match self {
+            Scalar::Ptr(_) => (dl.pointer_size, dl.pointer_align),
+            Scalar::Bits { size, .. } => {
+                let align = match size {
+                    0 | 1 => dl.i8_align,
+                    2 => dl.i16_align,
+                    4 => dl.i32_align,
+                    8 => dl.i64_align,
+                    16 => dl.i128_align,
+                    _ => bug!("invalid scalar size: {}", size),
+                };
+                (Size::from_bytes(size as u64), align)
+            }
+        }
+    }
+
+    /// Writes the scalar to `offset` in `alloc`, which has to be large enough to hold it.
+    fn write_to_alloc(self, alloc: &mut Allocation, offset: Size, cx: impl HasDataLayout) {
+        let (size, _) = self.size_and_align(cx);
+        // `write_target_uint` cannot handle zero-sized targets
+        if size.bytes() == 0 {
+            return;
+        }
+        let bits = match self {
+            Scalar::Bits { bits, .. } => bits,
+            Scalar::Ptr(ptr) => {
+                alloc.relocations.insert(offset, ptr.alloc_id);
+                ptr.offset.bytes() as u128
+            }
+        };
+        let end = offset + size;
+        let bytes = &mut alloc.bytes[offset.bytes() as usize..end.bytes() as usize];
+        write_target_uint(cx.data_layout().endian, bytes, bits).unwrap();
+        alloc.undef_mask.set_range_inbounds(offset, end, true);
+    }
+
+    /// Returns the `size` bytes making up the value, in the endianness of the target.
+    pub fn to_bytes(self, cx: impl HasDataLayout) -> EvalResult<'tcx, Vec<u8>> {
+        let (bits, size) = self.to_bits_any()?;
+        let mut bytes = vec![0; size.bytes() as usize];
+        // `write_target_uint` cannot handle zero-sized targets
+        if size.bytes() != 0 {
+            write_target_uint(cx.data_layout().endian, &mut bytes, bits).unwrap();
+        }
+        Ok(bytes)
+    }
+
+    /// Returns the byte at `index` of the bytes making up the value in the endianness of the
+    /// target, i.e. `to_bytes(cx)?[index]` without building the whole `Vec`.
+    pub fn byte_at(self, index: usize, cx: impl HasDataLayout) -> EvalResult<'tcx, u8> {
+        let (bits, size) = self.to_bits_any()?;
+        let len = size.bytes();
+        if index as u64 >= len {
+            return err!(BoundsCheck { len, index: index as u64 });
+        }
+        let shift = match cx.data_layout().endian {
+            layout::Endian::Little => index as u64,
+            layout::Endian::Big => len - 1 - index as u64,
+        };
+        Ok((bits >> (shift * 8)) as u8)
+    }
+
+    /// Whether both scalars are pointers into the same allocation, or `None` if either of them
+    /// is not a pointer.
+    #[inline]
+    pub fn same_alloc(self, other: Scalar) -> Option<bool> {
+        match (self, other) {
+            (Scalar::Ptr(a), Scalar::Ptr(b)) => Some(a.alloc_id == b.alloc_id),
+            _ => None,
+        }
+    }
+
+    /// Like `to_bits`, for when the caller knows this is not a pointer. Panics otherwise.
+    #[inline]
+    pub fn assert_bits(self, size: Size) -> u128 {
+        match self {
+            Scalar::Bits { bits, size: found } => {
+                if found as u64 != size.bytes() {
+                    bug!("expected {} bytes of raw bits, got {} bytes: {:?}",
+                         size.bytes(), found, self)
+                }
+                assert_ne!(found, 0, "assert_bits cannot be used with zsts");
+                bits
+            }
+            Scalar::Ptr(_) => bug!("expected {} bytes of raw bits, got {:?}", size.bytes(), self),
+        }
+    }
+
+    /// Whether the value, read as an unsigned integer, can be represented in `size` bytes
+    /// without losing information. Only 0 fits in zero bytes.
+    pub fn fits_in_unsigned(self, size: Size) -> EvalResult<'tcx, bool> {
+        let (bits, own_size) = self.to_bits_any()?;
+        assert_ne!(own_size.bytes(), 0, "fits_in_unsigned cannot be used with zsts");
+        // `truncate` cannot handle zsts, but only 0 fits in those anyway
+        if size.bytes() == 0 {
+            return Ok(bits == 0);
+        }
+        Ok(truncate(bits, size) == bits)
+    }
+
+    /// Whether the value, read as a signed integer of its own size, can be represented as a
+    /// signed integer of `size` bytes without losing information.
+    pub fn fits_in_signed(self, size: Size) -> EvalResult<'tcx, bool> {
+        let (bits, own_size) = self.to_bits_any()?;
+        assert_ne!(own_size.bytes(), 0, "fits_in_signed cannot be used with zsts");
+        let val = sign_extend(bits, own_size);
+        if size.bytes() == 0 {
+            return Ok(val == 0);
+        }
+        Ok(sign_extend(truncate(val, size), size) == val)
+    }
+}
+
+impl From<Pointer> for Scalar {
+    #[inline(always)]
+    fn from(ptr: Pointer) -> Self {
+        Scalar::Ptr(ptr)
+    }
+}
+
+/// Defaults to the zero-sized `Scalar::zst()`, so as not to imply any particular integer width.
+impl Default for Scalar {
+    #[inline]
+    fn default() -> Self {
+        Scalar::zst()
+    }
+}
+
+impl From<bool> for Scalar {
+    #[inline(always)]
+    fn from(b: bool) -> Self {
+        Scalar::from_bool(b)
+    }
+}
+
+impl From<char> for Scalar {
+    #[inline(always)]
+    fn from(c: char) -> Self {
+        Scalar::from_char(c)
+    }
+}
+
+macro_rules! impl_try_from_scalar {
+    ($($ty:ty => $method:ident),*) => {$(
+        impl TryFrom<Scalar> for $ty {
+            type Error = EvalErrorKind<'static, u64>;
+
+            #[inline]
+            fn try_from(scalar: Scalar) -> Result<Self, Self::Error> {
+                scalar.$method().map_err(|err| err.kind)
+            }
+        }
+    )*}
+}
+
+impl_try_from_scalar! {
+    u8 => to_u8, u16 => to_u16, u32 => to_u32, u64 => to_u64, u128 => to_u128,
+    i8 => to_i8, i16 => to_i16, i32 => to_i32, i64 => to_i64, i128 => to_i128
+}
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Scalar::Bits { bits, size: 1 } => write!(f, "{} (1 byte)", bits),
+            Scalar::Bits { bits, size } => write!(f, "{} ({} bytes)", bits, size),
+            Scalar::Ptr(ptr) => write!(f, "alloc{}+{}", ptr.alloc_id, ptr.offset.bytes()),
+        }
+    }
+}
+
+/// A `Scalar` represents an immediate, primitive value existing outside of a
+/// `memory::Allocation`. It is in many ways like a small chunk of a `Allocation`, up to 8 bytes in
+/// size. Like a range of bytes in an `Allocation`, a `Scalar` can either represent the raw bytes
+/// of a simple value or a pointer into another `Allocation`
+///
+/// A `Scalar` is always fully initialized. Code reading from memory that may contain undefined
+/// bytes has to use `ScalarMaybeUndef` and check it before converting to a `Scalar`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, RustcEncodable, RustcDecodable, Hash)]
+pub enum Scalar<Id=AllocId> {
+    /// The raw bytes of a simple value.
+    Bits {
+        /// The first `size` bytes are the value.
+        /// Do not try to read less or more bytes that that. The remaining bytes must be 0.
+        size: u8,
+        bits: u128,
+    },
+
+    /// A pointer into an `Allocation`. An `Allocation` in the `memory` module has a list of
+    /// relocations, but a `Scalar` is only large enough to contain one, so we just represent the
+    /// relocation and its associated offset together as a `Pointer` here.
+    Ptr(Pointer<Id>),
+}
+
+/// The result of reading a `Scalar` from memory: either a fully defined scalar, or `Undef` if any
+/// of the bytes read were undefined. Use `not_undef` to get at the `Scalar`, which errors in the
+/// latter case.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, RustcEncodable, RustcDecodable, Hash)]
+pub enum ScalarMaybeUndef<Id=AllocId> {
+    Scalar(Scalar<Id>),
+    Undef,
+}
+
+impl From<Scalar> for ScalarMaybeUndef {
+    #[inline(always)]
+    fn from(s: Scalar) -> Self {
+        ScalarMaybeUndef::Scalar(s)
+    }
+}
+
+impl<'tcx> ScalarMaybeUndef {
+    /// Whether this value is entirely defined. Reads of memory with undefined bytes produce
+    /// `Undef`, so this should be checked before trusting anything obtained from a read.
+    #[inline]
+    pub fn is_fully_initialized(self) -> bool {
+        match self {
+            ScalarMaybeUndef::Scalar(_) => true,
+            ScalarMaybeUndef::Undef => false,
+        }
+    }
+
+    /// Asserts that a value read from memory is not `Undef`, for reads that must never see
+    /// undefined bytes. `Scalar` itself cannot represent undefined bytes, so this is where that
+    /// invariant gets checked when turning the result of a read into a `Scalar`.
+    /// Only checked in debug builds.
+    #[inline]
+    pub fn assert_initialized(self) {
+        debug_assert!(self.is_fully_initialized(), "read undefined bytes where none may exist");
+    }
+
+    #[inline]
+    pub fn not_undef(self) -> EvalResult<'static, Scalar> {
+        match self {
+            ScalarMaybeUndef::Scalar(scalar) => Ok(scalar),
+            ScalarMaybeUndef::Undef => err!(ReadUndefBytes(Size::from_bytes(0))),
+        }
+    }
+
+    /// Same as `not_undef`.
+    #[inline(always)]
+    pub fn to_scalar(self) -> EvalResult<'static, Scalar> {
+        self.not_undef()
+    }
+
+    #[inline(always)]
+    pub fn to_ptr(self) -> EvalResult<'tcx, Pointer> {
+        self.not_undef()?.to_ptr()
+    }
+
+    #[inline(always)]
+    pub fn to_bits(self, target_size: Size) -> EvalResult<'tcx, u128> {
+        self.not_undef()?.to_bits(target_size)
+    }
+
+    #[inline(always)]
+    pub fn to_bool(self) -> EvalResult<'tcx, bool> {
+        self.not_undef()?.to_bool()
+    }
+
+    #[inline(always)]
+    pub fn to_char(self) -> EvalResult<'tcx, char> {
+        self.not_undef()?.to_char()
+    }
+
+    #[inline(always)]
+    pub fn to_f32(self) -> EvalResult<'tcx, f32> {
+        self.not_undef()?.to_f32()
+    }
+
+    #[inline(always)]
+    pub fn to_f64(self) -> EvalResult<'tcx, f64> {
+        self.not_undef()?.to_f64()
+    }
+
+    #[inline(always)]
+    pub fn to_u8(self) -> EvalResult<'tcx, u8> {
+        self.not_undef()?.to_u8()
+    }
+
+    #[inline(always)]
+    pub fn to_u32(self) -> EvalResult<'tcx, u32> {
+        self.not_undef()?.to_u32()
+    }
+
+    #[inline(always)]
+    pub fn to_u64(self) -> EvalResult<'tcx, u64> {
+        self.not_undef()?.to_u64()
+    }
+
+    #[inline(always)]
+    pub fn to_usize(self, cx: impl HasDataLayout) -> EvalResult<'tcx, u64> {
+        self.not_undef()?.to_usize(cx)
+    }
+
+    #[inline(always)]
+    pub fn to_i8(self) -> EvalResult<'tcx, i8> {
+        self.not_undef()?.to_i8()
+    }
+
+    #[inline(always)]
+    pub fn to_i32(self) -> EvalResult<'tcx, i32> {
+        self.not_undef()?.to_i32()
+    }
+
+    #[inline(always)]
+    pub fn to_i64(self) -> EvalResult<'tcx, i64> {
+        self.not_undef()?.to_i64()
+    }
+
+    #[inline(always)]
+    pub fn to_isize(self, cx: impl HasDataLayout) -> EvalResult<'tcx, i64> {
+        self.not_undef()?.to_isize(cx)
+    }
+
+    #[inline(always)]
+    pub fn to_target_usize(self, cx: impl HasDataLayout) -> EvalResult<'tcx, u64> {
+        self.not_undef()?.to_target_usize(cx)
+    }
+
+    #[inline(always)]
+    pub fn to_target_isize(self, cx: impl HasDataLayout) -> EvalResult<'tcx, i64> {
+        self.not_undef()?.to_target_isize(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The unsigned integer `bits` stored in `size`.
+    fn uint(bits: u128, size: Size) -> Scalar {
+        Scalar::from_uint(bits, size)
+    }
+
+    /// The signed integer `i` stored in `size`.
+    fn int(i: i128, size: Size) -> Scalar {
+        Scalar::from_int(i, size)
+    }
+
+    #[test]
+    fn in_range() {
+        let size = Size::from_bytes(1);
+        assert!(uint(5, size).in_range(1, 10, size).unwrap());
+        assert!(uint(1, size).in_range(1, 10, size).unwrap());
+        assert!(uint(10, size).in_range(1, 10, size).unwrap());
+        assert!(!uint(0, size).in_range(1, 10, size).unwrap());
+        assert!(!uint(11, size).in_range(1, 10, size).unwrap());
+        assert!(uint(7, size).in_range(7, 7, size).unwrap());
+        assert!(!uint(8, size).in_range(7, 7, size).unwrap());
+
+        // wrapping ranges
+        assert!(uint(255, size).in_range(254, 1, size).unwrap());
+        assert!(uint(254, size).in_range(254, 1, size).unwrap());
+        assert!(uint(0, size).in_range(254, 1, size).unwrap());
+        assert!(uint(1, size).in_range(254, 1, size).unwrap());
+        assert!(!uint(2, size).in_range(254, 1, size).unwrap());
+        assert!(!uint(253, size).in_range(254, 1, size).unwrap());
+        // no gap at all
+        assert!(uint(100, size).in_range(1, 0, size).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "there are no scalars of 3 bytes")]
+    fn from_bytes_odd_size() {
+        Scalar::from_bytes(&[0; 3], &layout::TargetDataLayout::default());
+    }
+
+    #[test]
+    fn try_from() {
+        let size = Size::from_bytes(1);
+        assert_eq!(Scalar::try_from_uint(255u8, size), Some(uint(255, size)));
+        assert_eq!(Scalar::try_from_uint(256u16, size), None);
+        assert_eq!(Scalar::try_from_int(-128i8, size), Some(Scalar::from_int(-128, size)));
+        assert_eq!(Scalar::try_from_int(128i16, size), None);
+
+        assert_eq!(Scalar::try_from_uint(0u8, Size::ZERO), Some(Scalar::zst()));
+        assert_eq!(Scalar::try_from_uint(1u8, Size::ZERO), None);
+        assert_eq!(Scalar::try_from_int(0i8, Size::ZERO), Some(Scalar::zst()));
+        assert_eq!(Scalar::try_from_int(-1i8, Size::ZERO), None);
+    }
+
+    #[test]
+    fn to_char_lossy() {
+        let size = Size::from_bytes(4);
+        assert_eq!(uint('x' as u128, size).to_char_lossy().unwrap(), 'x');
+        assert_eq!(uint(0xd800, size).to_char_lossy().unwrap(), '\u{fffd}');
+        assert_eq!(Scalar::from_uint(b'x', Size::from_bytes(1)).to_char_lossy().unwrap(),
+                   '\u{fffd}');
+    }
+
+    #[test]
+    fn int_to_float() {
+        let f32_of = |v: u128, bytes: u64, signed: bool| {
+            Scalar::from_uint(v, Size::from_bytes(bytes))
+                .int_to_f32(Size::from_bytes(bytes), signed).unwrap()
+                .to_f32().unwrap()
+        };
+        let f64_of = |v: u128, bytes: u64, signed: bool| {
+            Scalar::from_uint(v, Size::from_bytes(bytes))
+                .int_to_f64(Size::from_bytes(bytes), signed).unwrap()
+                .to_f64().unwrap()
+        };
+        assert_eq!(f32_of(0, 4, false), 0.0);
+        assert_eq!(f32_of(0xff, 1, false), 255.0);
+        assert_eq!(f32_of(0xff, 1, true), -1.0);
+        assert_eq!(f32_of(0x80, 1, true), -128.0);
+        // not exactly representable, rounds to nearest with ties to even
+        assert_eq!(f32_of(16_777_217, 4, false), 16_777_216.0);
+        assert_eq!(f32_of(16_777_219, 4, false), 16_777_220.0);
+        assert_eq!(f32_of(u32::max_value() as u128, 4, false), 4_294_967_296.0);
+        assert_eq!(f32_of(u128::max_value(), 16, false), ::std::f32::INFINITY);
+        assert_eq!(f32_of(1 << 127, 16, true), -(2.0f32.powi(127)));
+
+        assert_eq!(f64_of((1 << 53) + 1, 8, false), 9_007_199_254_740_992.0);
+        assert_eq!(f64_of(u64::max_value() as u128, 8, true), -1.0);
+        assert_eq!(f64_of(u128::max_value(), 16, false), 2.0f64.powi(128));
+
+        let ptr = Scalar::Ptr(Pointer::new(AllocId(0), Size::ZERO));
+        assert!(ptr.int_to_f32(Size::from_bytes(8), false).is_err());
+    }
+
+    #[test]
+    fn float_to_int() {
+        use std::{f32, f64};
+        let from_f32 = |f: f32, bytes: u64, signed: bool| {
+            let size = Size::from_bytes(bytes);
+            let bits = Scalar::from_f32(f).f32_to_int(size, signed).unwrap().to_bits(size);
+            if signed { sign_extend(bits.unwrap(), size) as i128 } else { bits.unwrap() as i128 }
+        };
+        let from_f64 = |f: f64, bytes: u64, signed: bool| {
+            let size = Size::from_bytes(bytes);
+            let bits = Scalar::from_f64(f).f64_to_int(size, signed).unwrap().to_bits(size);
+            if signed { sign_extend(bits.unwrap(), size) as i128 } else { bits.unwrap() as i128 }
+        };
+        assert_eq!(from_f32(1.9, 1, false), 1);
+        assert_eq!(from_f32(-1.9, 1, true), -1);
+        assert_eq!(from_f32(-0.0, 1, true), 0);
+        assert_eq!(from_f32(-0.5, 1, false), 0);
+        // NaN
+        assert_eq!(from_f32(f32::NAN, 4, false), 0);
+        assert_eq!(from_f32(f32::NAN, 4, true), 0);
+        assert_eq!(from_f64(-f64::NAN, 8, true), 0);
+        // infinities
+        assert_eq!(from_f32(f32::INFINITY, 1, false), 255);
+        assert_eq!(from_f32(f32::NEG_INFINITY, 1, false), 0);
+        assert_eq!(from_f32(f32::INFINITY, 1, true), 127);
+        assert_eq!(from_f32(f32::NEG_INFINITY, 1, true), -128);
+        assert_eq!(from_f64(f64::INFINITY, 16, false), u128::max_value() as i128);
+        assert_eq!(from_f64(f64::NEG_INFINITY, 16, true), i128::min_value());
+        // at and just beyond the bounds
+        assert_eq!(from_f32(255.0, 1, false), 255);
+        assert_eq!(from_f32(255.9, 1, false), 255);
+        assert_eq!(from_f32(256.0, 1, false), 255);
+        assert_eq!(from_f32(-1.0, 1, false), 0);
+        assert_eq!(from_f32(127.9, 1, true), 127);
+        assert_eq!(from_f32(128.0, 1, true), 127);
+        assert_eq!(from_f32(-128.9, 1, true), -128);
+        assert_eq!(from_f32(-129.0, 1, true), -128);
+        assert_eq!(from_f32(2_147_483_648.0, 4, true), i32::max_value() as i128);
+        assert_eq!(from_f64(4_294_967_295.5, 4, false), u32::max_value() as i128);
+        assert_eq!(from_f64(4_294_967_296.0, 4, false), u32::max_value() as i128);
+        assert_eq!(from_f32(f32::MAX, 16, true), i128::max_value());
+
+        let ptr = Scalar::Ptr(Pointer::new(AllocId(0), Size::ZERO));
+        assert!(ptr.f64_to_int(Size::from_bytes(8), false).is_err());
+        assert!(Scalar::from_f32(1.0).f64_to_int(Size::from_bytes(8), false).is_err());
+    }
+
+    #[test]
+    fn float_cmp() {
+        use std::{f32, f64};
+        let cmp32 = |l: f32, r: f32| Scalar::from_f32(l).float_cmp_f32(Scalar::from_f32(r));
+        let cmp64 = |l: f64, r: f64| Scalar::from_f64(l).float_cmp_f64(Scalar::from_f64(r));
+        assert_eq!(cmp32(1.0, 2.0).unwrap(), Some(Ordering::Less));
+        assert_eq!(cmp32(2.0, 1.0).unwrap(), Some(Ordering::Greater));
+        assert_eq!(cmp32(0.0, -0.0).unwrap(), Some(Ordering::Equal));
+        assert_eq!(cmp32(f32::NEG_INFINITY, f32::MIN).unwrap(), Some(Ordering::Less));
+        assert_eq!(cmp32(f32::NAN, 1.0).unwrap(), None);
+        assert_eq!(cmp32(1.0, f32::NAN).unwrap(), None);
+        assert_eq!(cmp32(f32::NAN, f32::NAN).unwrap(), None);
+
+        assert_eq!(cmp64(-1.5, -1.5).unwrap(), Some(Ordering::Equal));
+        assert_eq!(cmp64(f64::INFINITY, f64::MAX).unwrap(), Some(Ordering::Greater));
+        assert_eq!(cmp64(f64::NAN, 0.0).unwrap(), None);
+        assert_eq!(cmp64(0.0, f64::NAN).unwrap(), None);
+        assert_eq!(cmp64(f64::NAN, f64::NAN).unwrap(), None);
+
+        assert!(Scalar::from_f32(1.0).float_cmp_f64(Scalar::from_f64(1.0)).is_err());
+        assert!(Scalar::from_f64(1.0).float_cmp_f64(Scalar::from_f32(1.0)).is_err());
+    }
+
+    #[test]
+    fn min_max() {
+        let size = Size::from_bytes(1);
+        let (a, b) = (uint(0xff, size), uint(0x01, size));
+        // 0xff is the largest unsigned, but -1 is the smaller signed value
+        assert_eq!(a.min_unsigned(b, size).unwrap(), b);
+        assert_eq!(a.max_unsigned(b, size).unwrap(), a);
+        assert_eq!(a.min_signed(b, size).unwrap(), a);
+        assert_eq!(a.max_signed(b, size).unwrap(), b);
+
+        let nan = Scalar::from_f64(::std::f64::NAN);
+        let one = Scalar::from_f64(1.0);
+        assert_eq!(nan.min_f64(one).unwrap(), one);
+        assert_eq!(one.min_f64(nan).unwrap(), one);
+        assert_eq!(nan.max_f64(one).unwrap(), one);
+        assert_eq!(one.max_f64(nan).unwrap(), one);
+        let two = Scalar::from_f64(2.0);
+        assert_eq!(one.min_f64(two).unwrap(), one);
+        assert_eq!(one.max_f64(two).unwrap(), two);
+    }
+
+    #[test]
+    fn clamp() {
+        use mir::interpret::EvalErrorKind::*;
+        let invalid_range = |res: EvalResult<'static, Scalar>| match res.unwrap_err().kind {
+            InvalidClampRange => true,
+            _ => false,
+        };
+
+        let size = Size::from_bytes(1);
+        let (lo, hi) = (uint(10, size), uint(20, size));
+        assert_eq!(uint(5, size).clamp_unsigned(lo, hi, size).unwrap(), lo);
+        assert_eq!(uint(15, size).clamp_unsigned(lo, hi, size).unwrap(), uint(15, size));
+        assert_eq!(uint(25, size).clamp_unsigned(lo, hi, size).unwrap(), hi);
+        assert!(invalid_range(uint(15, size).clamp_unsigned(hi, lo, size)));
+
+        let (lo, hi) = (int(-10, size), int(10, size));
+        assert_eq!(int(-128, size).clamp_signed(lo, hi, size).unwrap(), lo);
+        assert_eq!(int(-1, size).clamp_signed(lo, hi, size).unwrap(), int(-1, size));
+        assert_eq!(int(127, size).clamp_signed(lo, hi, size).unwrap(), hi);
+        assert!(invalid_range(int(0, size).clamp_signed(hi, lo, size)));
+
+        let f = Scalar::from_f64;
+        let (lo, hi, nan) = (f(-1.0), f(1.0), f(::std::f64::NAN));
+        assert_eq!(f(-2.0).clamp_f64(lo, hi).unwrap(), lo);
+        assert_eq!(f(0.5).clamp_f64(lo, hi).unwrap(), f(0.5));
+        assert_eq!(f(2.0).clamp_f64(lo, hi).unwrap(), hi);
+        assert_eq!(nan.clamp_f64(lo, hi).unwrap(), nan);
+        assert!(invalid_range(f(0.0).clamp_f64(hi, lo)));
+        assert!(invalid_range(f(0.0).clamp_f64(nan, hi)));
+        assert!(invalid_range(f(0.0).clamp_f64(lo, nan)));
+    }
+
+    #[test]
+    fn division() {
+        use mir::interpret::EvalErrorKind::*;
+        let size = Size::from_bytes(1);
+        let i = |v: i8| Scalar::from_int(v, size);
+        let kind = |res: EvalResult<'static, Scalar>| res.unwrap_err().kind;
+
+        assert_eq!(uint(200, size).checked_div_unsigned(uint(7, size), size).unwrap(),
+                   uint(28, size));
+        assert_eq!(uint(200, size).checked_rem_unsigned(uint(7, size), size).unwrap(),
+                   uint(4, size));
+        assert_eq!(uint(255, size).checked_div_unsigned(uint(255, size), size).unwrap(),
+                   uint(1, size));
+        assert!(match kind(uint(1, size).checked_div_unsigned(uint(0, size), size)) {
+            DivisionByZero => true,
+            _ => false,
+        });
+        assert!(match kind(uint(1, size).checked_rem_unsigned(uint(0, size), size)) {
+            RemainderByZero => true,
+            _ => false,
+        });
+
+        assert_eq!(int(-7, size).checked_div_signed(int(2, size), size).unwrap(), int(-3, size));
+        assert_eq!(int(-7, size).checked_rem_signed(int(2, size), size).unwrap(), int(-1, size));
+        assert_eq!(int(7, size).checked_rem_signed(int(-2, size), size).unwrap(), int(1, size));
+        assert_eq!(int(-128, size).checked_div_signed(int(1, size), size).unwrap(),
+                   int(-128, size));
+        assert_eq!(int(-127, size).checked_div_signed(int(-1, size), size).unwrap(),
+                   int(127, size));
+        assert!(match kind(int(5, size).checked_div_signed(int(0, size), size)) {
+            DivisionByZero => true,
+            _ => false,
+        });
+        assert!(match kind(int(5, size).checked_rem_signed(int(0, size), size)) {
+            RemainderByZero => true,
+            _ => false,
+        });
+        assert!(match kind(int(-128, size).checked_div_signed(int(-1, size), size)) {
+            Overflow(mir::BinOp::Div) => true,
+            _ => false,
+        });
+        assert!(match kind(int(-128, size).checked_rem_signed(int(-1, size), size)) {
+            Overflow(mir::BinOp::Rem) => true,
+            _ => false,
+        });
+
+        let size = Size::from_bytes(16);
+        let min = Scalar::from_int(i128::min_value(), size);
+        let minus_one = Scalar::from_int(-1, size);
+        assert!(min.checked_div_signed(minus_one, size).is_err());
+        assert_eq!(min.checked_div_signed(Scalar::from_int(2, size), size).unwrap(),
+                   Scalar::from_int(i128::min_value() / 2, size));
+    }
+
+    #[test]
+    fn bitmask() {
+        assert_eq!(super::bitmask(Size::ZERO), 0);
+        // sizes are whole bytes, so 8 bits is the smallest non-empty mask
+        assert_eq!(super::bitmask(Size::from_bytes(1)), 0xff);
+        // and a single bit rounds up to such a byte
+        assert_eq!(super::bitmask(Size::from_bits(1)), 0xff);
+        assert_eq!(super::bitmask(Size::from_bytes(4)), 0xffff_ffff);
+        assert_eq!(super::bitmask(Size::from_bytes(15)), u128::max_value() >> 8);
+        assert_eq!(super::bitmask(Size::from_bytes(16)), u128::max_value());
+    }
+
+    #[test]
+    fn pow() {
+        let size = Size::from_bytes(1);
+        for base in 0..=255u8 {
+            let mut expected = 1u8;
+            let mut oflo = false;
+            for exp in 0..10 {
+                if exp > 0 {
+                    let (result, o) = expected.overflowing_mul(base);
+                    expected = result;
+                    oflo |= o;
+                }
+                assert_eq!(uint(base.into(), size).checked_pow_unsigned(exp, size).unwrap(),
+                           (uint(expected.into(), size), oflo),
+                           "{} ** {}", base, exp);
+            }
+        }
+        // squaring the base once more after its last use would overflow
+        assert_eq!(uint(2, size).checked_pow_unsigned(7, size).unwrap(), (uint(128, size), false));
+        assert_eq!(uint(3, size).checked_pow_unsigned(5, size).unwrap(), (uint(243, size), false));
+        assert_eq!(uint(2, size).checked_pow_unsigned(8, size).unwrap(), (uint(0, size), true));
+
+        let size = Size::from_bytes(16);
+        assert_eq!(uint(2, size).checked_pow_unsigned(127, size).unwrap(),
+                   (uint(1 << 127, size), false));
+        assert_eq!(uint(2, size).checked_pow_unsigned(128, size).unwrap(), (uint(0, size), true));
+        assert_eq!(uint(3, size).checked_pow_unsigned(80, size).unwrap(),
+                   (uint(3u128.pow(80), size), false));
+        assert_eq!(uint(3, size).checked_pow_unsigned(81, size).unwrap(),
+                   (uint(3u128.pow(80).wrapping_mul(3), size), true));
+
+        let f = Scalar::from_f64;
+        assert_eq!(f(3.0).powi_f64(4).unwrap(), f(81.0));
+        assert_eq!(f(2.0).powi_f64(-2).unwrap(), f(0.25));
+        assert_eq!(f(-2.0).powi_f64(3).unwrap(), f(-8.0));
+        assert_eq!(f(::std::f64::NAN).powi_f64(0).unwrap(), f(1.0));
+        assert_eq!(f(10.0).powi_f64(400).unwrap(), f(::std::f64::INFINITY));
+        assert_eq!(f(2.0).powf_f64(f(10.0)).unwrap(), f(1024.0));
+        assert_eq!(f(2.0).powf_f64(f(-0.0)).unwrap(), f(1.0));
+        assert!(f(2.0).powf_f64(f(0.5)).is_err());
+        assert!(f(2.0).powf_f64(f(::std::f64::NAN)).is_err());
+        assert!(f(2.0).powf_f64(f(1e10)).is_err());
+        assert!(Scalar::from_f32(2.0).powi_f64(2).is_err());
+    }
+
+    #[test]
+    fn shifts() {
+        let size = Size::from_bytes(1);
+        assert_eq!(uint(0b1000_0001, size).shl(1, size).unwrap(), uint(0b0000_0010, size));
+        assert_eq!(uint(1, size).shl(0, size).unwrap(), uint(1, size));
+        assert_eq!(uint(1, size).shl(7, size).unwrap(), uint(0x80, size));
+        assert!(uint(1, size).shl(8, size).is_err());
+        assert!(uint(1, size).shl(u32::max_value(), size).is_err());
+
+        assert_eq!(uint(0x80, size).shr_unsigned(7, size).unwrap(), uint(1, size));
+        assert_eq!(uint(0x80, size).shr_unsigned(0, size).unwrap(), uint(0x80, size));
+        assert!(uint(0x80, size).shr_unsigned(8, size).is_err());
+
+        assert_eq!(uint(0x80, size).shr_signed(7, size).unwrap(), uint(0xff, size));
+        assert_eq!(uint(0x40, size).shr_signed(6, size).unwrap(), uint(1, size));
+        assert_eq!(uint(0x80, size).shr_signed(0, size).unwrap(), uint(0x80, size));
+        assert!(uint(0x80, size).shr_signed(8, size).is_err());
+
+        let size = Size::from_bytes(16);
+        assert_eq!(uint(1, size).shl(127, size).unwrap(), uint(1 << 127, size));
+        assert_eq!(uint(1 << 127, size).shr_signed(127, size).unwrap(),
+                   uint(u128::max_value(), size));
+        assert_eq!(uint(1 << 127, size).shr_unsigned(127, size).unwrap(), uint(1, size));
+        assert!(uint(1, size).shl(128, size).is_err());
+    }
+
+    #[test]
+    fn rotates() {
+        let size = Size::from_bytes(1);
+        let x = uint(0b1000_0011, size);
+        for &amount in &[0, 8, 16] {
+            assert_eq!(x.rotate_left(amount, size).unwrap(), x);
+            assert_eq!(x.rotate_right(amount, size).unwrap(), x);
+        }
+        assert_eq!(x.rotate_left(9, size).unwrap(), uint(0b0000_0111, size));
+        assert_eq!(x.rotate_right(9, size).unwrap(), uint(0b1100_0001, size));
+
+        let size = Size::from_bytes(16);
+        let x = uint(1 << 127 | 1, size);
+        for &amount in &[0, 128, 256] {
+            assert_eq!(x.rotate_left(amount, size).unwrap(), x);
+            assert_eq!(x.rotate_right(amount, size).unwrap(), x);
+        }
+        assert_eq!(x.rotate_left(129, size).unwrap(), uint(0b11, size));
+        assert_eq!(x.rotate_right(129, size).unwrap(), uint(0b11 << 126, size));
+    }
+
+    #[test]
+    fn bit_counts() {
+        let size = Size::from_bytes(1);
+        assert_eq!(uint(0, size).leading_zeros(size).unwrap(), 8);
+        assert_eq!(uint(0, size).trailing_zeros(size).unwrap(), 8);
+        assert_eq!(uint(1, size).leading_zeros(size).unwrap(), 7);
+        assert_eq!(uint(0x80, size).trailing_zeros(size).unwrap(), 7);
+        assert_eq!(uint(0xff, size).count_ones(size).unwrap(), 8);
+        assert_eq!(uint(0xff, size).count_zeros(size).unwrap(), 0);
+        assert_eq!(uint(0, size).count_zeros(size).unwrap(), 8);
+
+        let size = Size::from_bytes(16);
+        assert_eq!(uint(0, size).leading_zeros(size).unwrap(), 128);
+        assert_eq!(uint(0, size).trailing_zeros(size).unwrap(), 128);
+        assert_eq!(uint(1 << 127, size).leading_zeros(size).unwrap(), 0);
+        assert_eq!(uint(u128::max_value(), size).count_ones(size).unwrap(), 128);
+        assert_eq!(uint(u128::max_value(), size).count_zeros(size).unwrap(), 0);
+    }
+
+    #[test]
+    fn byte_and_bit_reversal() {
+        let size = Size::from_bytes(1);
+        assert_eq!(uint(0x12, size).swap_bytes(size).unwrap(), uint(0x12, size));
+        assert_eq!(uint(1, size).reverse_bits(size).unwrap(), uint(0x80, size));
+        let size = Size::from_bytes(2);
+        assert_eq!(uint(0x1234, size).swap_bytes(size).unwrap(), uint(0x3412, size));
+        assert_eq!(uint(1, size).reverse_bits(size).unwrap(), uint(0x8000, size));
+        let size = Size::from_bytes(4);
+        assert_eq!(uint(0x1234_5678, size).swap_bytes(size).unwrap(), uint(0x7856_3412, size));
+        assert_eq!(uint(0b1011, size).reverse_bits(size).unwrap(), uint(0xd000_0000, size));
+        let size = Size::from_bytes(8);
+        assert_eq!(uint(0x0102_0304_0506_0708, size).swap_bytes(size).unwrap(),
+                   uint(0x0807_0605_0403_0201, size));
+        assert_eq!(uint(1, size).reverse_bits(size).unwrap(), uint(1 << 63, size));
+
+        let size = Size::from_bytes(16);
+        let x = 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10;
+        assert_eq!(uint(x, size).swap_bytes(size).unwrap(), uint(x.swap_bytes(), size));
+        assert_eq!(uint(x, size).reverse_bits(size).unwrap(), uint(x.reverse_bits(), size));
+        assert_eq!(uint(1, size).reverse_bits(size).unwrap(), uint(1 << 127, size));
+        let max = uint(u128::max_value(), size);
+        assert_eq!(max.swap_bytes(size).unwrap(), max);
+        assert_eq!(max.reverse_bits(size).unwrap(), max);
+    }
+
+    #[test]
+    fn truncate_to() {
+        let x = Scalar::from_uint(0x1234u16, Size::from_bytes(2));
+        let size = Size::from_bytes(1);
+        assert_eq!(x.truncate_to(size).unwrap(), uint(0x34, size));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot truncate to a zst")]
+    fn truncate_to_zst() {
+        let _ = Scalar::from_uint(1u8, Size::from_bytes(1)).truncate_to(Size::ZERO);
+    }
+
+    #[test]
+    fn fits_in() {
+        let size = Size::from_bytes(2);
+        let (one, four) = (Size::from_bytes(1), Size::from_bytes(4));
+        assert!(uint(0xff, size).fits_in_unsigned(one).unwrap());
+        assert!(!uint(0x100, size).fits_in_unsigned(one).unwrap());
+        assert!(uint(0xffff, size).fits_in_unsigned(four).unwrap());
+        assert!(int(-128, size).fits_in_signed(one).unwrap());
+        assert!(!int(-129, size).fits_in_signed(one).unwrap());
+        assert!(!uint(0xff, size).fits_in_signed(one).unwrap());
+
+        // only 0 fits in zero bytes
+        assert!(uint(0, size).fits_in_unsigned(Size::ZERO).unwrap());
+        assert!(!uint(1, size).fits_in_unsigned(Size::ZERO).unwrap());
+        assert!(int(0, size).fits_in_signed(Size::ZERO).unwrap());
+        assert!(!int(-1, size).fits_in_signed(Size::ZERO).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 4 bytes of raw bits, got 1 bytes")]
+    fn assert_bits_size_mismatch() {
+        Scalar::from_uint(1u8, Size::from_bytes(1)).assert_bits(Size::from_bytes(4));
+    }
+
+    #[test]
+    fn saturating() {
+        let size = Size::from_bytes(1);
+        assert_eq!(int(100, size).saturating_add_signed(int(100, size), size).unwrap(),
+                   int(127, size));
+        assert_eq!(int(-100, size).saturating_add_signed(int(-100, size), size).unwrap(),
+                   int(-128, size));
+        assert_eq!(int(-100, size).saturating_sub_signed(int(100, size), size).unwrap(),
+                   int(-128, size));
+        assert_eq!(int(100, size).saturating_sub_signed(int(-100, size), size).unwrap(),
+                   int(127, size));
+        assert_eq!(int(-5, size).saturating_sub_signed(int(3, size), size).unwrap(), int(-8, size));
+
+        assert_eq!(uint(200, size).saturating_add_unsigned(uint(100, size), size).unwrap(),
+                   uint(255, size));
+        assert_eq!(uint(1, size).saturating_sub_unsigned(uint(2, size), size).unwrap(),
+                   uint(0, size));
+
+        let size = Size::from_bytes(16);
+        let max = Scalar::from_int(i128::max_value(), size);
+        let min = Scalar::from_int(i128::min_value(), size);
+        assert_eq!(max.saturating_add_signed(max, size).unwrap(), max);
+        assert_eq!(min.saturating_add_signed(min, size).unwrap(), min);
+        assert_eq!(min.saturating_sub_signed(max, size).unwrap(), min);
+    }
+
+    #[test]
+    fn signum() {
+        let size = Size::from_bytes(4);
+        assert_eq!(int(-7, size).signum_signed(size).unwrap(), int(-1, size));
+        assert_eq!(int(0, size).signum_signed(size).unwrap(), int(0, size));
+        assert_eq!(int(i32::max_value().into(), size).signum_signed(size).unwrap(), int(1, size));
+
+        let f = |v: f64| Scalar::from_f64(v).signum_f64().unwrap().to_f64().unwrap();
+        assert_eq!(f(3.5), 1.0);
+        assert_eq!(f(-0.0), -1.0);
+        assert_eq!(f(0.0), 1.0);
+        assert!(f(::std::f64::NAN).is_nan());
+        let f = |v: f32| Scalar::from_f32(v).signum_f32().unwrap().to_f32().unwrap();
+        assert_eq!(f(-0.0), -1.0);
+        assert!(f(::std::f32::NAN).is_nan());
+        assert!(Scalar::from_f32(1.0).signum_f64().is_err());
+    }
+
+    #[test]
+    fn copysign() {
+        let f = |v: f64, sign: f64| {
+            Scalar::from_f64(v).copysign_f64(Scalar::from_f64(sign)).unwrap().to_f64().unwrap()
+        };
+        assert_eq!(f(3.0, -0.0), -3.0);
+        assert_eq!(f(-3.0, 1.0), 3.0);
+        assert!(f(0.0, -1.0).is_sign_negative());
+        let nan = f(::std::f64::NAN, -1.0);
+        assert!(nan.is_nan() && nan.is_sign_negative());
+
+        let f = |v: f32, sign: f32| {
+            Scalar::from_f32(v).copysign_f32(Scalar::from_f32(sign)).unwrap().to_f32().unwrap()
+        };
+        assert_eq!(f(2.5, -7.0), -2.5);
+        assert!(Scalar::from_f32(1.0).copysign_f64(Scalar::from_f64(1.0)).is_err());
+    }
+
+    #[test]
+    fn eq_ignoring_provenance_overlapping_pointer() {
+        let dl = TargetDataLayout::default();
+        let mut with_ptr = Allocation::from_byte_aligned_bytes(&[0; 16]);
+        with_ptr.relocations.insert(Size::ZERO, AllocId(0));
+        let plain = Allocation::from_byte_aligned_bytes(&[0; 14]);
+        // the pointer starts before the compared bytes, but still overlaps with them
+        let partial = ConstValue::ByRef(AllocId(1), &with_ptr, Size::from_bytes(2));
+        let zeros = ConstValue::ByRef(AllocId(2), &plain, Size::ZERO);
+        assert!(partial.eq_ignoring_provenance(&partial, &dl));
+        assert!(!partial.eq_ignoring_provenance(&zeros, &dl));
+    }
+
+    #[test]
+    fn ptr_signed_offset_overflow() {
+        use mir::interpret::EvalErrorKind::*;
+        let dl = TargetDataLayout::default();
+        let size = dl.pointer_size;
+        let kind = |res: EvalResult<'static, Scalar>| res.unwrap_err().kind;
+
+        assert_eq!(uint(8, size).ptr_signed_offset(-8, &dl).unwrap(), uint(0, size));
+        assert!(match kind(uint(0, size).ptr_signed_offset(-1, &dl)) {
+            PointerArithOverflow => true,
+            _ => false,
+        });
+        let max = uint(u128::from(u64::max_value()), size);
+        assert!(match kind(max.ptr_signed_offset(1, &dl)) {
+            PointerArithOverflow => true,
+            _ => false,
+        });
+        // pointers into allocations still report an overflowing addition
+        let ptr = Scalar::Ptr(Pointer::new(AllocId(0), Size::ZERO));
+        assert!(match kind(ptr.ptr_signed_offset(-1, &dl)) {
+            Overflow(mir::BinOp::Add) => true,
+            _ => false,
+        });
+    }
 }
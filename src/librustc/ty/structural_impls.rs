@@ -501,6 +501,7 @@ impl<'a, 'tcx, O: Lift<'tcx>> Lift<'tcx> for interpret::EvalErrorKind<'a, O> {
             InvalidMemoryAccess => InvalidMemoryAccess,
             InvalidFunctionPointer => InvalidFunctionPointer,
             InvalidBool => InvalidBool,
+            InvalidNonZero => InvalidNonZero,
             InvalidDiscriminant(val) => InvalidDiscriminant(val),
             PointerOutOfBounds {
                 ptr,
@@ -512,6 +513,7 @@ impl<'a, 'tcx, O: Lift<'tcx>> Lift<'tcx> for interpret::EvalErrorKind<'a, O> {
             ReadBytesAsPointer => ReadBytesAsPointer,
             ReadForeignStatic => ReadForeignStatic,
             InvalidPointerMath => InvalidPointerMath,
+            PointerArithOverflow => PointerArithOverflow,
             ReadUndefBytes(offset) => ReadUndefBytes(offset),
             DeadLocal => DeadLocal,
             InvalidBoolOp(bop) => InvalidBoolOp(bop),
@@ -524,6 +526,17 @@ impl<'a, 'tcx, O: Lift<'tcx>> Lift<'tcx> for interpret::EvalErrorKind<'a, O> {
             },
             Intrinsic(ref s) => Intrinsic(s.clone()),
             InvalidChar(c) => InvalidChar(c),
+            InvalidFloatSize {
+                expected,
+                found,
+            } => InvalidFloatSize { expected, found },
+            ScalarSizeMismatch {
+                expected,
+                found,
+            } => ScalarSizeMismatch { expected, found },
+            UnevaluatedConstant => UnevaluatedConstant,
+            UnexpectedConstValueKind => UnexpectedConstValueKind,
+            InvalidClampRange => InvalidClampRange,
             StackFrameLimitReached => StackFrameLimitReached,
             OutOfTls => OutOfTls,
             TlsOutOfBounds => TlsOutOfBounds,
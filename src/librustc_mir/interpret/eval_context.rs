@@ -23,7 +23,7 @@ use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
 use rustc::ty::query::TyCtxtAt;
 use rustc_data_structures::indexed_vec::IndexVec;
 use rustc::mir::interpret::{
-    GlobalId, Scalar, FrameInfo, AllocId,
+    GlobalId, Scalar, ScalarMaybeUndef, FrameInfo, AllocId,
     EvalResult, EvalErrorKind,
     truncate, sign_extend,
 };
@@ -31,7 +31,7 @@ use rustc::mir::interpret::{
 use syntax::source_map::{self, Span};
 
 use super::{
-    Value, Operand, MemPlace, MPlaceTy, Place, ScalarMaybeUndef,
+    Value, Operand, MemPlace, MPlaceTy, Place,
     Memory, Machine
 };
 
@@ -34,16 +34,16 @@ fn numeric_intrinsic<'tcx>(
         Primitive::Int(integer, _) => integer.size(),
         _ => bug!("invalid `{}` argument: {:?}", name, bits),
     };
-    let extra = 128 - size.bits() as u128;
-    let bits_out = match name {
-        "ctpop" => bits.count_ones() as u128,
-        "ctlz" => bits.leading_zeros() as u128 - extra,
-        "cttz" => (bits << extra).trailing_zeros() as u128 - extra,
-        "bswap" => (bits << extra).swap_bytes(),
-        "bitreverse" => (bits << extra).reverse_bits(),
+    let scalar = Scalar::from_uint(bits, size);
+    let count = |n: u32| Scalar::from_uint(n, size);
+    match name {
+        "ctpop" => Ok(count(scalar.count_ones(size)?)),
+        "ctlz" => Ok(count(scalar.leading_zeros(size)?)),
+        "cttz" => Ok(count(scalar.trailing_zeros(size)?)),
+        "bswap" => scalar.swap_bytes(size),
+        "bitreverse" => scalar.reverse_bits(size),
         _ => bug!("not a numeric intrinsic: {}", name),
-    };
-    Ok(Scalar::from_uint(bits_out, size))
+    }
 }
 
 impl<'a, 'mir, 'tcx, M: Machine<'a, 'mir, 'tcx>> EvalContext<'a, 'mir, 'tcx, M> {
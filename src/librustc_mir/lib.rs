@@ -37,7 +37,6 @@ Rust MIR: a lowered representation of Rust. Also: an experiment!
 #![feature(slice_concat_ext)]
 #![feature(if_while_or_patterns)]
 #![feature(try_from)]
-#![feature(reverse_bits)]
 #![feature(underscore_imports)]
 
 #![recursion_limit="256"]
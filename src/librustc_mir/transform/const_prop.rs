@@ -18,10 +18,10 @@ use rustc::mir::{NullOp, UnOp, StatementKind, Statement, BasicBlock, LocalKind};
 use rustc::mir::{TerminatorKind, ClearCrossCrate, SourceInfo, BinOp, ProjectionElem};
 use rustc::mir::visit::{Visitor, PlaceContext};
 use rustc::mir::interpret::{
-    ConstEvalErr, EvalErrorKind, Scalar, GlobalId, EvalResult
+    ConstEvalErr, EvalErrorKind, Scalar, ScalarMaybeUndef, GlobalId, EvalResult
 };
 use rustc::ty::{TyCtxt, self, Instance};
-use interpret::{self, EvalContext, Value, OpTy, MemoryKind, ScalarMaybeUndef};
+use interpret::{self, EvalContext, Value, OpTy, MemoryKind};
 use const_eval::{CompileTimeInterpreter, eval_promoted, mk_borrowck_eval_cx};
 use transform::{MirPass, MirSource};
 use syntax::source_map::{Span, DUMMY_SP};
@@ -171,6 +171,7 @@ impl<'a, 'mir, 'tcx> ConstPropagator<'a, 'mir, 'tcx> {
                     | DoubleFree
                     | InvalidFunctionPointer
                     | InvalidBool
+                    | InvalidNonZero
                     | InvalidDiscriminant(..)
                     | PointerOutOfBounds { .. }
                     | InvalidNullPointerUsage
@@ -180,6 +181,7 @@ impl<'a, 'mir, 'tcx> ConstPropagator<'a, 'mir, 'tcx> {
                     | InvalidMemoryLockRelease { .. }
                     | DeallocatedLockedMemory { .. }
                     | InvalidPointerMath
+                    | PointerArithOverflow
                     | ReadUndefBytes(_)
                     | DeadLocal
                     | InvalidBoolOp(_)
@@ -187,6 +189,10 @@ impl<'a, 'mir, 'tcx> ConstPropagator<'a, 'mir, 'tcx> {
                     | ExecuteMemory
                     | Intrinsic(..)
                     | InvalidChar(..)
+                    | InvalidFloatSize { .. }
+                    | ScalarSizeMismatch { .. }
+                    | UnexpectedConstValueKind
+                    | InvalidClampRange
                     | AbiViolation(_)
                     | AlignmentCheckFailed{..}
                     | CalledClosureAsFunction
@@ -222,6 +228,7 @@ impl<'a, 'mir, 'tcx> ConstPropagator<'a, 'mir, 'tcx> {
                     | UnimplementedTraitSelection
                     | TypeckError
                     | TooGeneric
+                    | UnevaluatedConstant
                     | CheckMatchError
                     // these are just noise
                     => {},